@@ -0,0 +1,102 @@
+use crate::error::AlphabetError;
+
+///
+/// Symbols used to encode data, with the reverse lookup used to decode it
+///
+/// Symbols must be unique, printable ASCII other than the `=` padding
+/// character.  As `new` is a `const fn`, alphabets can be validated and their
+/// reverse lookups generated at compile time.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Alphabet<const N: usize> {
+    pub(crate) symbols: [u8; N],
+    // reverse lookup that maps:
+    // - symbols back to their values (0 to N-1)
+    // - padding to N
+    // - whitespace to 254
+    // - garbage to 255
+    pub(crate) reverse: [u8; 256],
+}
+
+/// Alphabet of 64 symbols, for base-64
+pub type B64Alphabet = Alphabet<64>;
+
+/// Alphabet of 32 symbols, for base-32
+pub type B32Alphabet = Alphabet<32>;
+
+impl<const N: usize> Alphabet<N> {
+    ///
+    /// Create an alphabet, validating its symbols
+    ///
+    /// # Arguments
+    ///
+    /// * `symbols` - Symbol for each value, in order
+    ///
+    pub const fn new(symbols: &[u8; N]) -> Result<Self, AlphabetError> {
+        let mut reverse = [255; 256];
+
+        // whitespace is skipped, and padding marks the end of the data
+        let mut c = b'\t';
+        while c <= b'\r' {
+            reverse[c as usize] = 254;
+            c += 1;
+        }
+        reverse[b' ' as usize] = 254;
+        reverse[b'=' as usize] = N as u8;
+
+        // each symbol maps back to its value
+        let mut i = 0;
+        while i < N {
+            let symbol = symbols[i];
+            if symbol == b'=' {
+                return Err(AlphabetError::PaddingSymbol);
+            } else if !symbol.is_ascii_graphic() {
+                return Err(AlphabetError::InvalidSymbol(symbol));
+            } else if reverse[symbol as usize] != 255 {
+                return Err(AlphabetError::DuplicateSymbol(symbol));
+            }
+            reverse[symbol as usize] = i as u8;
+            i += 1;
+        }
+
+        Ok(Alphabet {
+            symbols: *symbols,
+            reverse,
+        })
+    }
+
+    ///
+    /// Symbol for each value, in order
+    ///
+    pub const fn symbols(&self) -> &[u8; N] {
+        &self.symbols
+    }
+}
+
+// unwrap an alphabet known to be valid, failing compilation if it is not
+const fn known_valid<const N: usize>(alphabet: Result<Alphabet<N>, AlphabetError>) -> Alphabet<N> {
+    match alphabet {
+        Ok(alphabet) => alphabet,
+        Err(_) => panic!("invalid alphabet"),
+    }
+}
+
+impl Alphabet<64> {
+    /// The standard base-64 alphabet (RFC 4648, section 4)
+    pub const STANDARD: Self = known_valid(Alphabet::new(
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/",
+    ));
+
+    /// The URL and filename safe base-64 alphabet (RFC 4648, section 5)
+    pub const URL_SAFE: Self = known_valid(Alphabet::new(
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_",
+    ));
+}
+
+impl Alphabet<32> {
+    /// The standard base-32 alphabet (RFC 4648, section 6)
+    pub const STANDARD: Self = known_valid(Alphabet::new(b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567"));
+
+    /// The "extended hex" base-32 alphabet (RFC 4648, section 7)
+    pub const EXTENDED_HEX: Self = known_valid(Alphabet::new(b"0123456789ABCDEFGHIJKLMNOPQRSTUV"));
+}
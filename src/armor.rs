@@ -0,0 +1,494 @@
+use std::io::{Read, Write};
+
+use crate::config::B64Config;
+use crate::error::{relocate_error, Position};
+use crate::pem::{boundary, is_valid_header};
+
+// initial value of CRC-24 checksums (RFC 4880, section 6.1)
+const CRC24_INIT: u32 = 0xB704CE;
+
+// label of the BEGIN line of a clearsigned message, which has no END line
+const CLEARSIGNED: &str = "PGP SIGNED MESSAGE";
+
+///
+/// Type of data in an OpenPGP armor block (RFC 4880, section 6.2)
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArmorKind {
+    /// `PGP MESSAGE`
+    Message,
+    /// `PGP PUBLIC KEY BLOCK`
+    PublicKeyBlock,
+    /// `PGP PRIVATE KEY BLOCK`
+    PrivateKeyBlock,
+    /// `PGP SIGNATURE`
+    Signature,
+}
+
+impl ArmorKind {
+    ///
+    /// Label used in the block's BEGIN and END lines
+    ///
+    pub const fn label(self) -> &'static str {
+        match self {
+            ArmorKind::Message => "PGP MESSAGE",
+            ArmorKind::PublicKeyBlock => "PGP PUBLIC KEY BLOCK",
+            ArmorKind::PrivateKeyBlock => "PGP PRIVATE KEY BLOCK",
+            ArmorKind::Signature => "PGP SIGNATURE",
+        }
+    }
+
+    ///
+    /// Type of data with a label, if known
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - Label from a BEGIN or END line
+    ///
+    pub fn from_label(label: &str) -> Option<Self> {
+        match label {
+            "PGP MESSAGE" => Some(ArmorKind::Message),
+            "PGP PUBLIC KEY BLOCK" => Some(ArmorKind::PublicKeyBlock),
+            "PGP PRIVATE KEY BLOCK" => Some(ArmorKind::PrivateKeyBlock),
+            "PGP SIGNATURE" => Some(ArmorKind::Signature),
+            _ => None,
+        }
+    }
+}
+
+///
+/// An OpenPGP armor block
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArmorBlock {
+    /// Type of data
+    pub kind: ArmorKind,
+    /// Armor headers (e.g. "Version", "Comment"), in order
+    pub headers: Vec<(String, String)>,
+    /// Decoded data
+    pub data: Vec<u8>,
+}
+
+///
+/// A clearsigned message (RFC 4880, section 7)
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClearSigned {
+    /// Headers of the signed message (e.g. "Hash")
+    pub headers: Vec<(String, String)>,
+    /// Signed text, without dash-escaping
+    pub text: Vec<u8>,
+    /// Signature over the text
+    pub signature: ArmorBlock,
+}
+
+///
+/// Compute the CRC-24 checksum of data, as used by armor checksum lines
+///
+/// # Arguments
+///
+/// * `data` - Data to checksum
+///
+pub fn crc24(data: &[u8]) -> u32 {
+    crc24_update(CRC24_INIT, data)
+}
+
+// continue a CRC-24 checksum over more data
+fn crc24_update(mut crc: u32, data: &[u8]) -> u32 {
+    const POLY: u32 = 0x1864CFB;
+
+    for &byte in data {
+        crc ^= (byte as u32) << 16;
+        for _ in 0..8 {
+            crc <<= 1;
+            if crc & 0x1000000 != 0 {
+                crc ^= POLY;
+            }
+        }
+    }
+    crc & 0xFFFFFF
+}
+
+// reader that computes the CRC-24 checksum of the data read through it
+struct Crc24Reader<R: Read> {
+    reader: R,
+    crc: u32,
+}
+
+impl<R: Read> Read for Crc24Reader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.reader.read(buf)?;
+        self.crc = crc24_update(self.crc, &buf[0..n]);
+        Ok(n)
+    }
+}
+
+///
+/// Encode data as an OpenPGP armor block, with no headers
+///
+/// # Arguments
+///
+/// * `reader` - Data to encode
+/// * `writer` - Writer to which the armor block will be written
+/// * `kind` - Type of data
+///
+pub fn armor_encode(
+    reader: &mut impl Read,
+    writer: &mut impl Write,
+    kind: ArmorKind,
+) -> Result<(), std::io::Error> {
+    write_block(kind, &[], reader, writer)
+}
+
+///
+/// Encode an OpenPGP armor block, including any headers and the checksum line
+///
+/// # Arguments
+///
+/// * `block` - Type, headers and data to encode
+/// * `writer` - Writer to which the armor block will be written
+///
+pub fn armor_encode_block(
+    block: &ArmorBlock,
+    writer: &mut impl Write,
+) -> Result<(), std::io::Error> {
+    let mut output = Vec::new();
+    write_block(
+        block.kind,
+        &block.headers,
+        &mut block.data.as_slice(),
+        &mut output,
+    )?;
+    writer.write_all(&output)
+}
+
+///
+/// Decode all OpenPGP armor blocks, verifying their checksums
+///
+/// Text outside of blocks is skipped.  A clearsigned message contributes
+/// only its signature block; use `armor_clearsign_decode` for its text.
+/// Errors in the base-64 data are reported as a `DecodeError` located within
+/// the whole input.
+///
+/// # Arguments
+///
+/// * `reader` - Armor reader
+///
+pub fn armor_decode(reader: &mut impl Read) -> Result<Vec<ArmorBlock>, std::io::Error> {
+    // the blocks are returned whole, and base-64 errors are located by each line's
+    // offset in the whole input, so split the input into lines up front
+    let mut input = Vec::new();
+    reader.read_to_end(&mut input)?;
+    let lines = split_lines(&input);
+
+    let mut blocks = Vec::new();
+    let mut index = 0;
+    while index < lines.len() {
+        let Some(label) = boundary(lines[index].1, "BEGIN") else {
+            index += 1;
+            continue;
+        };
+        if label == CLEARSIGNED {
+            let (message, next) = parse_clearsigned(&lines, index)?;
+            blocks.push(message.signature);
+            index = next;
+            continue;
+        }
+        let kind = ArmorKind::from_label(&label)
+            .ok_or_else(|| invalid(index, &format!("unknown armor type {}", label)))?;
+        let (block, next) = parse_block(&lines, index, kind)?;
+        blocks.push(block);
+        index = next;
+    }
+
+    Ok(blocks)
+}
+
+///
+/// Encode a clearsigned message, dash-escaping the text
+///
+/// # Arguments
+///
+/// * `message` - Headers, text and signature to encode
+/// * `writer` - Writer to which the message will be written
+///
+pub fn armor_clearsign_encode(
+    message: &ClearSigned,
+    writer: &mut impl Write,
+) -> Result<(), std::io::Error> {
+    // sanity-check parameters
+    if message.signature.kind != ArmorKind::Signature {
+        return Err(std::io::Error::other(
+            "clearsigned messages need a signature",
+        ));
+    }
+
+    let mut output = Vec::new();
+    writeln!(output, "-----BEGIN {}-----", CLEARSIGNED)?;
+    write_headers(&message.headers, &mut output)?;
+    writeln!(output)?;
+
+    // escape lines that start with a dash, so they can't be mistaken for armor
+    for line in message.text.split(|&c| c == b'\n') {
+        if line.starts_with(b"-") {
+            output.extend_from_slice(b"- ");
+        }
+        output.extend_from_slice(line);
+        output.push(b'\n');
+    }
+
+    let signature = &message.signature;
+    write_block(
+        signature.kind,
+        &signature.headers,
+        &mut signature.data.as_slice(),
+        &mut output,
+    )?;
+    writer.write_all(&output)
+}
+
+///
+/// Decode a clearsigned message, undoing dash-escaping and verifying the
+/// signature's checksum
+///
+/// The signature itself is not verified.  The line ending before the
+/// signature is not part of the signed text, so is removed.
+///
+/// # Arguments
+///
+/// * `reader` - Clearsigned message reader
+///
+pub fn armor_clearsign_decode(reader: &mut impl Read) -> Result<ClearSigned, std::io::Error> {
+    // the line ending before the signature is only known not to be part of the text
+    // once the signature is found, so read the whole message before decoding any of it
+    let mut input = Vec::new();
+    reader.read_to_end(&mut input)?;
+    let lines = split_lines(&input);
+
+    // skip anything before the BEGIN line
+    let begin = lines
+        .iter()
+        .position(|(_, line)| boundary(line, "BEGIN").as_deref() == Some(CLEARSIGNED))
+        .ok_or_else(|| std::io::Error::other("missing BEGIN PGP SIGNED MESSAGE line"))?;
+    let (message, _) = parse_clearsigned(&lines, begin)?;
+    Ok(message)
+}
+
+// error for an invalid line
+fn invalid(line_index: usize, reason: &str) -> std::io::Error {
+    std::io::Error::other(format!(
+        "invalid armor on line {}: {}",
+        line_index + 1,
+        reason
+    ))
+}
+
+// parse the clearsigned message whose BEGIN line is at `begin`, returning it and the
+// index of the line after its signature
+fn parse_clearsigned(
+    lines: &[(usize, &[u8])],
+    begin: usize,
+) -> Result<(ClearSigned, usize), std::io::Error> {
+    let (headers, mut index) = parse_headers(lines, begin + 1);
+
+    // collect the text up to the signature
+    let mut text = Vec::new();
+    loop {
+        let Some(&(_, line)) = lines.get(index) else {
+            return Err(invalid(begin, "missing signature"));
+        };
+        if let Some(label) = boundary(line, "BEGIN") {
+            if label != ArmorKind::Signature.label() {
+                return Err(invalid(
+                    index,
+                    &format!("expected BEGIN PGP SIGNATURE, found BEGIN {}", label),
+                ));
+            }
+            break;
+        }
+        text.extend_from_slice(line.strip_prefix(b"- ").unwrap_or(line));
+        index += 1;
+    }
+    if text.ends_with(b"\r\n") {
+        text.truncate(text.len() - 2);
+    } else if text.ends_with(b"\n") {
+        text.truncate(text.len() - 1);
+    }
+
+    let (signature, next) = parse_block(lines, index, ArmorKind::Signature)?;
+    Ok((
+        ClearSigned {
+            headers,
+            text,
+            signature,
+        },
+        next,
+    ))
+}
+
+// each line of the input, including its line ending, with its offset
+fn split_lines(input: &[u8]) -> Vec<(usize, &[u8])> {
+    let mut offset = 0;
+    input
+        .split_inclusive(|&c| c == b'\n')
+        .map(|line| {
+            offset += line.len();
+            (offset - line.len(), line)
+        })
+        .collect()
+}
+
+// write headers, one per line
+fn write_headers(
+    headers: &[(String, String)],
+    output: &mut impl Write,
+) -> Result<(), std::io::Error> {
+    if !headers
+        .iter()
+        .all(|(name, value)| is_valid_header(name, value))
+    {
+        return Err(std::io::Error::other("invalid armor header"));
+    }
+    for (name, value) in headers {
+        writeln!(output, "{}: {}", name, value)?;
+    }
+    Ok(())
+}
+
+// write an armor block of data read from `reader`, with its checksum
+fn write_block(
+    kind: ArmorKind,
+    headers: &[(String, String)],
+    reader: &mut impl Read,
+    output: &mut impl Write,
+) -> Result<(), std::io::Error> {
+    writeln!(output, "-----BEGIN {}-----", kind.label())?;
+
+    // headers are separated from the data by a blank line, even if there are none
+    write_headers(headers, output)?;
+    writeln!(output)?;
+
+    // armored data is wrapped at 64 columns like PEM, as by GnuPG
+    let mut reader = Crc24Reader {
+        reader,
+        crc: CRC24_INIT,
+    };
+    B64Config::PEM.encode(&mut reader, output)?;
+    let checksum = reader.crc.to_be_bytes();
+    writeln!(
+        output,
+        "={}",
+        B64Config::STANDARD.encode_to_string(&checksum[1..])?
+    )?;
+    writeln!(output, "-----END {}-----", kind.label())?;
+    Ok(())
+}
+
+// parse headers starting at line `index`, returning them and the index of the first
+// line after them
+fn parse_headers(lines: &[(usize, &[u8])], mut index: usize) -> (Vec<(String, String)>, usize) {
+    let mut headers = Vec::new();
+    while let Some(&(_, line)) = lines.get(index) {
+        let text = line.trim_ascii();
+
+        // headers should end with a blank line, but base-64 never contains a colon
+        if text.is_empty() {
+            index += 1;
+            break;
+        }
+        let Some(colon) = text.iter().position(|&c| c == b':') else {
+            break;
+        };
+        headers.push((
+            String::from_utf8_lossy(text[0..colon].trim_ascii()).into_owned(),
+            String::from_utf8_lossy(text[colon + 1..].trim_ascii()).into_owned(),
+        ));
+        index += 1;
+    }
+    (headers, index)
+}
+
+// parse an armor block whose BEGIN line is at `begin`, returning it and the index
+// of the line after its END line
+fn parse_block(
+    lines: &[(usize, &[u8])],
+    begin: usize,
+    kind: ArmorKind,
+) -> Result<(ArmorBlock, usize), std::io::Error> {
+    let (headers, mut index) = parse_headers(lines, begin + 1);
+
+    // collect encoded data and the checksum up to the END line
+    let mut encoded = Vec::new();
+    let mut encoded_start: Option<Position> = None;
+    let mut checksum: Option<(usize, u32)> = None;
+    loop {
+        let Some(&(offset, line)) = lines.get(index) else {
+            return Err(invalid(
+                begin,
+                &format!("missing END line for {}", kind.label()),
+            ));
+        };
+        if let Some(label) = boundary(line, "END") {
+            if label != kind.label() {
+                return Err(invalid(
+                    index,
+                    &format!("BEGIN {} ended by END {}", kind.label(), label),
+                ));
+            }
+            break;
+        }
+        if checksum.is_some() {
+            return Err(invalid(index, "data after checksum"));
+        }
+
+        // base-64 lines never start with padding, so this can only be the checksum
+        let text = line.trim_ascii();
+        if let Some(encoded_checksum) = text.strip_prefix(b"=") {
+            let bytes = B64Config::PEM
+                .decode_to_vec(encoded_checksum)
+                .ok()
+                .filter(|bytes| bytes.len() == 3)
+                .ok_or_else(|| invalid(index, "invalid checksum"))?;
+            checksum = Some((index, u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]])));
+        } else {
+            // remember where the encoded data starts, to locate any errors
+            if encoded_start.is_none() {
+                encoded_start = Some(Position {
+                    offset,
+                    line: index + 1,
+                    column: 1,
+                });
+            }
+            encoded.extend_from_slice(line);
+        }
+        index += 1;
+    }
+
+    // decode the data, reporting errors where they are in the input
+    let data = B64Config::PEM
+        .decode_to_vec(&encoded)
+        .map_err(|error| relocate_error(error, encoded_start))?;
+
+    // the checksum is optional, but must match if present
+    if let Some((checksum_index, expected)) = checksum {
+        let actual = crc24(&data);
+        if actual != expected {
+            return Err(invalid(
+                checksum_index,
+                &format!(
+                    "checksum mismatch (expected {:06X}, computed {:06X})",
+                    expected, actual
+                ),
+            ));
+        }
+    }
+
+    Ok((
+        ArmorBlock {
+            kind,
+            headers,
+            data,
+        },
+        index + 1,
+    ))
+}
@@ -1,33 +1,180 @@
-use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
-
-use crate::common::wrapping_write;
-
-// the canonical base-64 alphabet
-const ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
-
-// reverse lookup that maps:
-// - base-32 chars back to their values (0-31)
-// - base-32 padding to 32
-// - whitespace to 254
-// - garbage to 255
-const REVERSE_ALPHABET: [u8; 256] = [
-    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFE, 0xFE, 0xFE, 0xFE, 0xFE, 0xFF, 0xFF,
-    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
-    0xFE, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
-    0xFF, 0xFF, 0x1A, 0x1B, 0x1C, 0x1D, 0x1E, 0x1F, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x20, 0xFF, 0xFF,
-    0xFF, 0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D, 0x0E,
-    0x0F, 0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
-    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
-    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
-    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
-    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
-    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
-    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
-    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
-    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
-    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
-    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
-];
+use std::io::{BufRead, BufReader, Read, Write};
+
+use crate::alphabet::B32Alphabet;
+use crate::config::{B32Config, DecodePolicy, LineEnding, Padding};
+use crate::error::{DecodeError, Position, PositionTracker};
+use crate::line_wrapper::LineWrapper;
+
+impl B32Config {
+    ///
+    /// Decode base-32 encoded data
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - Base-32 encoded data reader
+    /// * `writer` - Writer to which decoded data will be written
+    ///
+    pub fn decode(
+        &self,
+        reader: &mut impl Read,
+        writer: &mut impl Write,
+    ) -> Result<(), std::io::Error> {
+        // wrap the reader in a buffered reader
+        let mut buf_reader = BufReader::with_capacity(self.buffer_size, reader);
+
+        self.decode_buf(&mut buf_reader, writer)
+    }
+
+    ///
+    /// Decode base-32 encoded data from a buffered reader
+    ///
+    /// Input is consumed only as far as it is decoded, so with
+    /// `DecodePolicy::StopAtPadding` anything following the first padded word is
+    /// left in the reader.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - Base-32 encoded data reader
+    /// * `writer` - Writer to which decoded data will be written
+    ///
+    pub fn decode_buf(
+        &self,
+        reader: &mut impl BufRead,
+        writer: &mut impl Write,
+    ) -> Result<(), std::io::Error> {
+        // internal write buffering to minimize calls to the writer
+        let mut write_buffer = vec![0; self.buffer_size / 8 * 5 + 5];
+
+        // partial word and position, carried from one buffer to the next
+        let mut decoder = B32Decoder::with_config(self);
+
+        // loop through data
+        loop {
+            // request a new buffer from the reader, limited to what the write buffer can hold
+            let buffer = reader.fill_buf()?;
+            let buffer = &buffer[0..buffer.len().min(self.buffer_size)];
+
+            // exit loop if no more data
+            if buffer.is_empty() {
+                break;
+            }
+
+            // decode and output the buffer
+            let (consumed, write_index) = decoder.decode_buffer(buffer, &mut write_buffer)?;
+            writer.write_all(&write_buffer[0..write_index])?;
+
+            // inform reader that the bytes have been consumed
+            reader.consume(consumed);
+
+            // nothing more to do once decoding has stopped
+            if decoder.finished {
+                return Ok(());
+            }
+        }
+
+        // decode any unpadded final word
+        let write_index = decoder.finish(&mut write_buffer)?;
+        writer.write_all(&write_buffer[0..write_index])
+    }
+
+    ///
+    /// Decode base-32 encoded data into a new vector
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - Base-32 encoded data
+    ///
+    pub fn decode_to_vec(&self, input: &[u8]) -> Result<Vec<u8>, std::io::Error> {
+        let mut output = Vec::with_capacity(b32_decoded_len_estimate(input.len()));
+        let mut decoder = B32Decoder::with_config(self);
+        decoder.update(input, &mut output)?;
+        decoder.finalize(&mut output)?;
+        Ok(output)
+    }
+
+    ///
+    /// Encode data in base-32
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - Data to encode
+    /// * `writer` - Writer to which encoded data will be written
+    ///
+    pub fn encode(
+        &self,
+        reader: &mut impl Read,
+        writer: &mut impl Write,
+    ) -> Result<(), std::io::Error> {
+        // read and write buffers
+        let mut read_buffer = vec![0; self.buffer_size];
+        let mut write_buffer = vec![0; self.buffer_size / 5 * 8 + 8];
+
+        // leftover bytes and output column, carried from one buffer to the next
+        let mut encoder = B32Encoder::with_config(self)?;
+        let mut lines = encoder.line_wrapper(writer);
+
+        loop {
+            // fill read buffer
+            let bytes_read = reader.read(&mut read_buffer)?;
+
+            // if out of data, exit loop
+            if bytes_read == 0 {
+                break;
+            }
+
+            // encode and output the buffer
+            encoder.encode_buffer(&read_buffer[0..bytes_read], &mut write_buffer, &mut lines)?;
+        }
+
+        encoder.finish(&mut lines)?;
+        lines.end()?;
+        Ok(())
+    }
+
+    ///
+    /// Encode data in base-32 as a string
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - Data to encode
+    ///
+    pub fn encode_to_string(&self, input: &[u8]) -> Result<String, std::io::Error> {
+        let mut encoder = B32Encoder::with_config(self)?;
+        let mut output = Vec::with_capacity(self.encoded_len(input.len()).unwrap_or(0));
+        encoder.update(input, &mut output);
+        encoder.finalize(&mut output);
+        Ok(String::from_utf8(output).expect("base-32 is ASCII"))
+    }
+
+    ///
+    /// Exact length of the base-32 encoding of data of a given length
+    ///
+    /// This includes any padding and, when wrapping, the line ending after
+    /// each line.  Returns `None` if the length overflows, or the wrap column
+    /// is 0.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - Length of the data
+    ///
+    pub fn encoded_len(&self, n: usize) -> Option<usize> {
+        let chars = if self.padding == Padding::Required {
+            n.div_ceil(5).checked_mul(8)?
+        } else {
+            // unpadded, the final word has only as many chars as it needs to hold its bits
+            (n / 5).checked_mul(8)? + (n % 5 * 8).div_ceil(5)
+        };
+        match self.wrap {
+            None => Some(chars),
+            Some(0) => None,
+            // a line ending after each line, of which there are none for empty data
+            Some(wrap) => {
+                let lines = chars.div_ceil(wrap);
+                chars.checked_add(lines.checked_mul(self.line_ending.as_bytes().len())?)
+            }
+        }
+    }
+}
 
 ///
 /// Decode base-32 encoded data
@@ -36,142 +183,602 @@ const REVERSE_ALPHABET: [u8; 256] = [
 ///
 /// * `reader` - Base-32 encoded data reader
 /// * `writer` - Writer to which decoded data will be written
-/// * `ignore_garbage` - Whether or not invalid characters should be ignored
+/// * `policy` - Decoding policy, or whether or not invalid characters should be ignored
 ///
 pub fn b32_decode(
     reader: &mut impl Read,
     writer: &mut impl Write,
-    ignore_garbage: bool,
+    policy: impl Into<DecodePolicy>,
 ) -> Result<(), std::io::Error> {
-    // wrap the reader in a 64KiB buffered reader
-    let mut buf_reader = BufReader::with_capacity(65536, reader);
+    B32Config::STANDARD
+        .decode_policy(policy.into())
+        .decode(reader, writer)
+}
 
-    // internal write buffering to minimize calls to the writer
-    let mut write_buffer: [u8; 65536] = [0; 65536];
-    let mut write_index: usize = 0;
+///
+/// Decode base-32 encoded data from a buffered reader
+///
+/// Input is consumed only as far as it is decoded, so with
+/// `DecodePolicy::StopAtPadding` anything following the first padded word is
+/// left in the reader.
+///
+/// # Arguments
+///
+/// * `reader` - Base-32 encoded data reader
+/// * `writer` - Writer to which decoded data will be written
+/// * `policy` - Decoding policy, or whether or not invalid characters should be ignored
+///
+pub fn b32_decode_buf(
+    reader: &mut impl BufRead,
+    writer: &mut impl Write,
+    policy: impl Into<DecodePolicy>,
+) -> Result<(), std::io::Error> {
+    B32Config::STANDARD
+        .decode_policy(policy.into())
+        .decode_buf(reader, writer)
+}
 
-    // temp buffer for 8-char base-32 `word` to be decoded
-    let mut word: [u8; 8] = [0; 8];
-    let mut word_index: usize = 0;
+///
+/// Decode base-32 encoded data into a slice
+///
+/// Returns the number of bytes decoded, which is at most
+/// `b32_decoded_len_estimate(input.len())`.
+///
+/// # Arguments
+///
+/// * `input` - Base-32 encoded data
+/// * `output` - Slice to which decoded data will be written
+/// * `policy` - Decoding policy, or whether or not invalid characters should be ignored
+///
+pub fn b32_decode_to_slice(
+    input: &[u8],
+    output: &mut [u8],
+    policy: impl Into<DecodePolicy>,
+) -> Result<usize, std::io::Error> {
+    let mut decoder = B32Decoder::new(policy);
+    let mut write_buffer: [u8; 4096] = [0; 4096];
+    let mut written: usize = 0;
+
+    // decode a chunk at a time, copying into the slice if it fits; chunks leave room in the
+    // write buffer for a word carried over from the previous chunk
+    for chunk in input.chunks(4096 / 5 * 8 - 8) {
+        let (_, n) = decoder.decode_buffer(chunk, &mut write_buffer)?;
+        output
+            .get_mut(written..written + n)
+            .ok_or_else(|| std::io::Error::other("output buffer too small"))?
+            .copy_from_slice(&write_buffer[0..n]);
+        written += n;
 
+        // nothing more to do once decoding has stopped
+        if decoder.finished {
+            break;
+        }
+    }
+
+    // decode any unpadded final word
+    let n = decoder.finish(&mut write_buffer)?;
+    output
+        .get_mut(written..written + n)
+        .ok_or_else(|| std::io::Error::other("output buffer too small"))?
+        .copy_from_slice(&write_buffer[0..n]);
+    Ok(written + n)
+}
+
+///
+/// Decode base-32 encoded data into a new vector
+///
+/// # Arguments
+///
+/// * `input` - Base-32 encoded data
+/// * `policy` - Decoding policy, or whether or not invalid characters should be ignored
+///
+pub fn b32_decode_to_vec(
+    input: &[u8],
+    policy: impl Into<DecodePolicy>,
+) -> Result<Vec<u8>, std::io::Error> {
+    B32Config::STANDARD
+        .decode_policy(policy.into())
+        .decode_to_vec(input)
+}
+
+///
+/// Maximum number of bytes that base-32 encoded data of a given length can decode to
+///
+/// The actual length is less if the data contains padding or whitespace.
+///
+/// # Arguments
+///
+/// * `n` - Length of the encoded data
+///
+pub fn b32_decoded_len_estimate(n: usize) -> usize {
+    n.div_ceil(8) * 5
+}
+
+///
+/// Reader that decodes base-32 encoded data from another reader
+///
+/// Decoding is performed a buffer at a time as data is read, so the decoded
+/// stream can be passed to anything that accepts a `Read`.
+///
+pub struct B32DecoderReader<R: Read> {
+    reader: BufReader<R>,
+    decoder: B32Decoder,
+    // decoded data not yet returned by `read`
+    output: Vec<u8>,
+    output_start: usize,
+    output_end: usize,
+    // whether or not there is no more data to decode
+    done: bool,
+}
+
+impl<R: Read> B32DecoderReader<R> {
+    ///
+    /// Wrap a reader
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - Base-32 encoded data reader
+    /// * `policy` - Decoding policy, or whether or not invalid characters should be ignored
+    ///
+    pub fn new(reader: R, policy: impl Into<DecodePolicy>) -> Self {
+        B32DecoderReader::with_config(reader, &B32Config::STANDARD.decode_policy(policy.into()))
+    }
+
+    ///
+    /// Wrap a reader, decoding with the given settings
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - Base-32 encoded data reader
+    /// * `config` - Decoding settings
+    ///
+    pub fn with_config(reader: R, config: &B32Config) -> Self {
+        B32DecoderReader {
+            reader: BufReader::with_capacity(config.buffer_size, reader),
+            decoder: B32Decoder::with_config(config),
+            output: vec![0; config.buffer_size / 8 * 5 + 5],
+            output_start: 0,
+            output_end: 0,
+            done: false,
+        }
+    }
+}
+
+impl<R: Read> Read for B32DecoderReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        // decode more input once all decoded data has been returned
+        while self.output_start == self.output_end {
+            if self.done {
+                return Ok(0);
+            }
+
+            // at the end of input, decode any unpadded final word
+            let buffer = self.reader.fill_buf()?;
+            let n = buffer.len();
+            if n == 0 {
+                self.done = true;
+                self.output_end = self.decoder.finish(&mut self.output)?;
+                self.output_start = 0;
+                continue;
+            }
+
+            let (consumed, written) = self.decoder.decode_buffer(buffer, &mut self.output)?;
+            self.output_start = 0;
+            self.output_end = written;
+            self.done = self.decoder.finished;
+            self.reader.consume(consumed);
+        }
+
+        // return as much decoded data as will fit
+        let n = buf.len().min(self.output_end - self.output_start);
+        buf[0..n].copy_from_slice(&self.output[self.output_start..self.output_start + n]);
+        self.output_start += n;
+        Ok(n)
+    }
+}
+
+///
+/// Incremental base-32 decoder
+///
+/// Encoded data can be passed to `update` in fragments of any size, with the
+/// partial word left over from one fragment carried to the next.
+///
+pub struct B32Decoder {
+    // temp buffer for 8-char base-32 `word` to be decoded
+    word: [u8; 8],
+    word_index: usize,
     // whether or not the final padded word has already been decoded
-    let mut reached_end = false;
+    reached_end: bool,
+    // whether or not the remaining input can be skipped
+    finished: bool,
+    policy: DecodePolicy,
+    padding: Padding,
+    strict: bool,
+    alphabet: B32Alphabet,
+    // position of the current buffer in the input, for error reporting
+    tracker: PositionTracker,
+    // position of the last data char in earlier buffers, tracked in strict mode
+    last_data: Position,
+}
 
-    // loop through data
-    loop {
-        // request a new buffer from the reader
-        let buffer = buf_reader.fill_buf()?;
+impl B32Decoder {
+    ///
+    /// Create a decoder
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - Decoding policy, or whether or not invalid characters should be ignored
+    ///
+    pub fn new(policy: impl Into<DecodePolicy>) -> Self {
+        B32Decoder::with_config(&B32Config::STANDARD.decode_policy(policy.into()))
+    }
 
-        // exit loop if no more data
-        let n = buffer.len();
-        if n == 0 {
-            break;
+    ///
+    /// Create a decoder with the given settings
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Decoding settings
+    ///
+    pub fn with_config(config: &B32Config) -> Self {
+        B32Decoder {
+            word: [0; 8],
+            word_index: 0,
+            reached_end: false,
+            finished: false,
+            policy: config.policy,
+            padding: config.padding,
+            strict: config.strict,
+            alphabet: config.alphabet,
+            tracker: PositionTracker::new(),
+            last_data: Position {
+                offset: 0,
+                line: 1,
+                column: 1,
+            },
         }
+    }
+
+    ///
+    /// Decode a fragment of encoded data
+    ///
+    /// Returns the number of bytes of the fragment consumed, which is all of it
+    /// unless decoding has stopped at a padded word under
+    /// `DecodePolicy::StopAtPadding`.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - Fragment of encoded data
+    /// * `output` - Vector to which decoded data will be appended
+    ///
+    pub fn update(&mut self, input: &[u8], output: &mut Vec<u8>) -> Result<usize, std::io::Error> {
+        // once finished, input is either left unconsumed or skipped
+        if self.finished {
+            return Ok(if self.policy == DecodePolicy::StopAtPadding {
+                0
+            } else {
+                input.len()
+            });
+        }
+
+        // make room for the decoded data, then trim to what was decoded
+        let start = output.len();
+        output.resize(start + input.len() / 8 * 5 + 5, 0);
+        match self.decode_buffer(input, &mut output[start..]) {
+            Ok((consumed, n)) => {
+                output.truncate(start + n);
+                Ok(consumed)
+            }
+            Err(error) => {
+                output.truncate(start);
+                Err(error)
+            }
+        }
+    }
+
+    ///
+    /// Whether or not decoding has stopped, ignoring any further input
+    ///
+    /// This happens after the first padded word under
+    /// `DecodePolicy::IgnoreGarbage` and `DecodePolicy::StopAtPadding`.
+    ///
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    ///
+    /// Decode any unpadded final word, and check that the encoded data did not
+    /// end part way through a word
+    ///
+    /// # Arguments
+    ///
+    /// * `output` - Vector to which decoded data will be appended
+    ///
+    pub fn finalize(mut self, output: &mut Vec<u8>) -> Result<(), std::io::Error> {
+        let mut write_buffer: [u8; 5] = [0; 5];
+        let n = self.finish(&mut write_buffer)?;
+        output.extend_from_slice(&write_buffer[0..n]);
+        Ok(())
+    }
+
+    // decode a buffer of input, returning the number of bytes consumed and the number written
+    // to `write_buffer`,
+    // which must have room for 5 bytes for every 8 bytes of input, plus 5
+    fn decode_buffer(
+        &mut self,
+        buffer: &[u8],
+        write_buffer: &mut [u8],
+    ) -> Result<(usize, usize), std::io::Error> {
+        // work on local copies of the state, so they can be kept in registers
+        let mut word = self.word;
+        let mut word_index = self.word_index;
+        let mut reached_end = self.reached_end;
+        // chars at the start of the word already checked for errors, having been carried
+        // from an earlier buffer or kept when cleaning
+        let mut checked = self.word_index;
+        let policy = self.policy;
+        let strict = self.strict;
+        let reverse = &self.alphabet.reverse;
+        let tracker = &self.tracker;
+        let mut write_index: usize = 0;
 
         // for each byte in the buffer
-        for &b in buffer {
+        for (index, &b) in buffer.iter().enumerate() {
             // decode the character and add to the word
-            let decoded_value: u8 = REVERSE_ALPHABET[b as usize];
+            let decoded_value: u8 = reverse[b as usize];
             word[word_index] = decoded_value;
             word_index += 1;
 
             // if full word try to decode
             if word_index == 8 {
                 // if all bytes are valid (happy path)
-                if (word[0] | word[1] | word[2] | word[3] | word[4] | word[5] | word[6] | word[7]) < 32 && !reached_end {
+                if (word[0] | word[1] | word[2] | word[3] | word[4] | word[5] | word[6] | word[7])
+                    < 32
+                    && !reached_end
+                {
                     // decode and output word
                     write_buffer[write_index] = (word[0] << 3) | (word[1] >> 2);
-                    write_buffer[write_index + 1] = (word[1] << 6) | (word[2] << 1) | (word[3] >> 4);
+                    write_buffer[write_index + 1] =
+                        (word[1] << 6) | (word[2] << 1) | (word[3] >> 4);
                     write_buffer[write_index + 2] = (word[3] << 4) | (word[4] >> 1);
-                    write_buffer[write_index + 3] = (word[4] << 7) | (word[5] << 2) | (word[6] >> 3);
+                    write_buffer[write_index + 3] =
+                        (word[4] << 7) | (word[5] << 2) | (word[6] >> 3);
                     write_buffer[write_index + 4] = (word[6] << 5) | word[7];
                     write_index += 5;
                     word_index = 0;
+                    checked = 0;
                 } else {
+                    // report the first error in the chars not yet checked, which are the most
+                    // recent input, ending at this byte
+                    let unchecked = &word[checked..word_index];
+                    if let Some(error) = self.first_error(unchecked, reached_end, buffer, index + 1)
+                    {
+                        return Err(error);
+                    }
+
+                    // once the final padded word has been decoded, only whitespace may follow
+                    if reached_end {
+                        word_index = 0;
+                        checked = 0;
+                        continue;
+                    }
+
                     // clean out garbage and whitespace
-                    let mut i = 0;
-                    while i < word_index {
-                        match word[i] {
-                            254 => {
-                                // whitespace: shift word data left to replace it
-                                word.copy_within((i + 1)..8, i);
-                                word_index -= 1;
-                            }
-                            255 => {
-                                // garbage: either eliminate or error out depending on ignore_garbage flag
-                                if ignore_garbage {
-                                    word.copy_within((i + 1)..8, i);
-                                    word_index -= 1;
-                                } else {
-                                    return Err(std::io::Error::new(
-                                        ErrorKind::Other,
-                                        "invalid input",
-                                    ));
-                                }
-                            }
-                            _ => {
-                                // valid char or padding: keep it
-                                i += 1;
-                            }
+                    let mut j = 0;
+                    while j < word_index {
+                        if word[j] >= 254 {
+                            // shift word data left to replace it
+                            word.copy_within((j + 1)..8, j);
+                            word_index -= 1;
+                        } else {
+                            // valid char or padding: keep it
+                            j += 1;
                         }
                     }
+                    checked = word_index;
 
                     // if still full, must be the final padded word
                     if word_index == 8 {
-                        // if already process final word, then this is garbage, and if we were
-                        // ignoring garbage we already would have returned, so this is an error
-                        if reached_end {
-                            return Err(std::io::Error::new(ErrorKind::Other, "invalid input"));
-                        }
-
                         // we've reached the end
                         reached_end = true;
                         word_index = 0;
+                        checked = 0;
+
+                        // count the data chars preceding the padding
+                        let data_chars = word.iter().position(|&c| c == 32).unwrap_or(8);
 
                         // output final bytes
-                        if word[0] == 64 || word[1] == 64 || word[3] != 64 {
-                            // if either of firt two chars are padding, or the last char isn't, that's garbage
-                            return Err(std::io::Error::new(ErrorKind::Other, "invalid input"));
-                        } else if word[2] == 64 {
-                            // if two padding chars, output final byte
-                            write_buffer[write_index] = (word[0] << 2) | (word[1] >> 4);
-                            write_index += 1;
-                        } else {
-                            // if one padding char, output final two byte
-                            write_buffer[write_index] = (word[0] << 2) | (word[1] >> 4);
-                            write_buffer[write_index + 1] = (word[1] << 4) | (word[2] >> 2);
-                            write_index += 2;
+                        let final_bytes = match data_chars {
+                            2 => 1,
+                            4 => 2,
+                            5 => 3,
+                            7 => 4,
+                            // no other amount of padding can be produced by an encoder
+                            _ => 0,
+                        };
+                        if final_bytes == 0 || !word[data_chars..].iter().all(|&c| c == 32) {
+                            // invalid amount of padding, or data following padding, is garbage
+                            return Err(tracker.invalid_padding(buffer, index));
+                        }
+
+                        // in strict mode, bits of the last data char that are not decoded must be zero
+                        if strict && unused_bits(&word, data_chars) != 0 {
+                            return Err(self.non_canonical_trailing_bits(buffer, index));
+                        }
+
+                        // decode the word with padding treated as zeros, keeping only the final bytes
+                        for c in word[data_chars..].iter_mut() {
+                            *c = 0;
                         }
+                        let decoded: [u8; 5] = [
+                            (word[0] << 3) | (word[1] >> 2),
+                            (word[1] << 6) | (word[2] << 1) | (word[3] >> 4),
+                            (word[3] << 4) | (word[4] >> 1),
+                            (word[4] << 7) | (word[5] << 2) | (word[6] >> 3),
+                            (word[6] << 5) | word[7],
+                        ];
+                        write_buffer[write_index..write_index + final_bytes]
+                            .copy_from_slice(&decoded[0..final_bytes]);
+                        write_index += final_bytes;
 
-                        // if ignoring garbage, nothing after this matters to just return
-                        if ignore_garbage {
-                            writer.write_all(&write_buffer[0..write_index])?;
-                            return Ok(());
+                        match policy {
+                            // if ignoring garbage, nothing after this matters
+                            DecodePolicy::IgnoreGarbage => {
+                                self.finished = true;
+                                return Ok((buffer.len(), write_index));
+                            }
+                            // stop, leaving the rest of the buffer unconsumed
+                            DecodePolicy::StopAtPadding => {
+                                self.finished = true;
+                                return Ok((index + 1, write_index));
+                            }
+                            // start the next segment
+                            DecodePolicy::Concatenated => reached_end = false,
+                            _ => {}
                         }
                     }
                 }
             }
         }
 
-        // output write buffer
-        writer.write_all(&write_buffer[0..write_index])?;
-        write_index = 0;
+        // report the first error in the chars of a partial word not yet checked before leaving
+        // this buffer, as they are the most recent input, ending at the end of the buffer
+        let unchecked = &word[checked..word_index];
+        if let Some(error) = self.first_error(unchecked, reached_end, buffer, buffer.len()) {
+            return Err(error);
+        }
+
+        // in strict mode, remember the last data char, as the trailing bits of a final word
+        // ending in it may only be checked in a later buffer
+        if strict {
+            if let Some(i) = buffer.iter().rposition(|&c| reverse[c as usize] < 32) {
+                self.last_data = tracker.locate(buffer, i);
+            }
+        }
+
+        // save state for the next buffer
+        self.word = word;
+        self.word_index = word_index;
+        self.reached_end = reached_end;
+        self.tracker.advance(buffer);
 
-        // inform reader that the bytes have been consumed
-        buf_reader.consume(n);
+        Ok((buffer.len(), write_index))
     }
 
-    // if there is extraneous data and won't be ignored
-    if word_index != 0 && !ignore_garbage {
-        // if any of it is garbage, return an error
-        for i in 0..word_index {
-            if word[i] == 255 {
-                return Err(std::io::Error::new(ErrorKind::Other, "invalid input"));
+    // error for the first of `chars`, which end before `end` in the buffer, that is garbage
+    // (unless it is being ignored), whitespace if not allowed, data after the final padded
+    // word or forbidden padding, if any
+    fn first_error(
+        &self,
+        chars: &[u8],
+        reached_end: bool,
+        buffer: &[u8],
+        end: usize,
+    ) -> Option<std::io::Error> {
+        // lowest reverse alphabet entry that is an error, unless ignoring garbage
+        let min_invalid = if self.policy == DecodePolicy::NoWhitespace {
+            254
+        } else {
+            255
+        };
+        let ignore_garbage = self.policy == DecodePolicy::IgnoreGarbage;
+        let start = end - chars.len();
+        chars.iter().enumerate().find_map(|(j, &c)| {
+            if c >= min_invalid && !ignore_garbage {
+                Some(self.tracker.invalid_byte(buffer, start + j))
+            } else if reached_end && c != 254 {
+                Some(self.tracker.data_after_padding(buffer, start + j))
+            } else if c == 32 && self.padding == Padding::Forbidden {
+                Some(self.tracker.invalid_padding(buffer, start + j))
+            } else {
+                None
             }
+        })
+    }
+
+    // error for non-zero trailing bits in the last data char before `index` in the buffer,
+    // which may have been in an earlier buffer
+    #[cold]
+    fn non_canonical_trailing_bits(&self, buffer: &[u8], index: usize) -> std::io::Error {
+        let reverse = &self.alphabet.reverse;
+        match buffer[0..index]
+            .iter()
+            .rposition(|&c| reverse[c as usize] < 32)
+        {
+            Some(i) => self.tracker.non_canonical_trailing_bits(buffer, i),
+            None => DecodeError::NonCanonicalTrailingBits {
+                position: self.last_data,
+            }
+            .into(),
         }
     }
 
-    Ok(())
+    // check the end of input, decoding any unpadded final word into `write_buffer`
+    fn finish(&mut self, write_buffer: &mut [u8]) -> Result<usize, std::io::Error> {
+        // nothing after the final padded word matters once finished
+        if self.finished {
+            return Ok(0);
+        }
+
+        // collect the data remaining after whitespace and garbage are removed
+        let mut word: [u8; 8] = [0; 8];
+        let mut data_chars = 0;
+        for &c in self.word[0..self.word_index].iter().filter(|&&c| c < 254) {
+            word[data_chars] = c;
+            data_chars += 1;
+        }
+        self.word_index = 0;
+        if data_chars == 0 {
+            return Ok(0);
+        }
+
+        // any remaining data is an incomplete word, unless padding may be omitted and the
+        // amount of data is one an encoder can produce
+        let final_bytes = match data_chars {
+            2 => 1,
+            4 => 2,
+            5 => 3,
+            7 => 4,
+            _ => 0,
+        };
+        if self.padding == Padding::Required || final_bytes == 0 || word.contains(&32) {
+            return Err(DecodeError::TruncatedInput {
+                position: self.tracker.end(),
+            }
+            .into());
+        }
+
+        // in strict mode, bits of the last data char that are not decoded must be zero
+        if self.strict && unused_bits(&word, data_chars) != 0 {
+            return Err(DecodeError::NonCanonicalTrailingBits {
+                position: self.last_data,
+            }
+            .into());
+        }
+
+        // decode the word with the missing chars treated as zeros, keeping only the final bytes
+        let decoded: [u8; 5] = [
+            (word[0] << 3) | (word[1] >> 2),
+            (word[1] << 6) | (word[2] << 1) | (word[3] >> 4),
+            (word[3] << 4) | (word[4] >> 1),
+            (word[4] << 7) | (word[5] << 2) | (word[6] >> 3),
+            (word[6] << 5) | word[7],
+        ];
+        write_buffer[0..final_bytes].copy_from_slice(&decoded[0..final_bytes]);
+        Ok(final_bytes)
+    }
+}
+
+// bits of the last data char of a final word of `data_chars` chars that are not decoded
+fn unused_bits(word: &[u8; 8], data_chars: usize) -> u8 {
+    let mask = match data_chars {
+        2 => 0x3,
+        4 => 0xF,
+        5 => 0x1,
+        7 => 0x7,
+        _ => return 0,
+    };
+    word[data_chars - 1] & mask
 }
 
 pub fn b32_encode(
@@ -179,128 +786,346 @@ pub fn b32_encode(
     writer: &mut impl Write,
     wrap: Option<usize>,
 ) -> Result<(), std::io::Error> {
-    // sanity-check parameters
-    if wrap == Some(0) {
-        return Err(std::io::Error::new(
-            ErrorKind::Other,
-            "cannot wrap on column 0",
-        ));
+    B32Config::STANDARD.wrap(wrap).encode(reader, writer)
+}
+
+///
+/// Encode data in base-32 into a slice
+///
+/// Returns the number of bytes written, which is exactly
+/// `b32_encoded_len(input.len(), wrap)`.
+///
+/// # Arguments
+///
+/// * `input` - Data to encode
+/// * `output` - Slice to which encoded data will be written
+/// * `wrap` - Column at which to wrap encoded data
+///
+pub fn b32_encode_to_slice(
+    input: &[u8],
+    output: &mut [u8],
+    wrap: Option<usize>,
+) -> Result<usize, std::io::Error> {
+    let mut encoder = B32Encoder::new(wrap)?;
+
+    // check the output fits before writing any of it
+    let len = b32_encoded_len(input.len(), wrap)
+        .filter(|&len| len <= output.len())
+        .ok_or_else(|| std::io::Error::other("output buffer too small"))?;
+
+    let mut lines = encoder.line_wrapper(&mut output[0..len]);
+    encoder.encode_all(input, &mut lines)?;
+    encoder.finish(&mut lines)?;
+    lines.end()?;
+    Ok(len)
+}
+
+///
+/// Encode data in base-32 as a string
+///
+/// # Arguments
+///
+/// * `input` - Data to encode
+/// * `wrap` - Column at which to wrap encoded data
+///
+pub fn b32_encode_to_string(input: &[u8], wrap: Option<usize>) -> Result<String, std::io::Error> {
+    B32Config::STANDARD.wrap(wrap).encode_to_string(input)
+}
+
+///
+/// Exact length of the base-32 encoding of data of a given length
+///
+/// This includes padding and, when wrapping, the newline ending each line.
+/// Returns `None` if the length overflows, or `wrap` is column 0.
+///
+/// # Arguments
+///
+/// * `n` - Length of the data
+/// * `wrap` - Column at which to wrap encoded data
+///
+pub fn b32_encoded_len(n: usize, wrap: Option<usize>) -> Option<usize> {
+    B32Config::STANDARD.wrap(wrap).encoded_len(n)
+}
+
+///
+/// Writer that base-32 encodes data before passing it to another writer
+///
+/// Any leftover bytes are encoded by `finish`.  If the writer is
+/// dropped without calling `finish` this happens automatically, but any error
+/// is lost.
+///
+pub struct B32EncoderWriter<W: Write> {
+    // taken by `finish`
+    writer: Option<LineWrapper<W>>,
+    encoder: B32Encoder,
+    write_buffer: Vec<u8>,
+    // most input encoded into the write buffer at once
+    buffer_size: usize,
+}
+
+impl<W: Write> B32EncoderWriter<W> {
+    ///
+    /// Wrap a writer
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - Writer to which encoded data will be written
+    /// * `wrap` - Column at which to wrap encoded data
+    ///
+    pub fn new(writer: W, wrap: Option<usize>) -> Result<Self, std::io::Error> {
+        B32EncoderWriter::with_config(writer, &B32Config::STANDARD.wrap(wrap))
     }
 
-    // read and write buffers and indecies
-    let mut read_buffer: [u8; 65535] = [0; 65535];
-    let mut read_index: usize = 0;
-    let mut write_buffer: [u8; 104856] = [0; 104856];
-    let mut write_index: usize = 0;
+    ///
+    /// Wrap a writer, encoding with the given settings
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - Writer to which encoded data will be written
+    /// * `config` - Encoding settings
+    ///
+    pub fn with_config(writer: W, config: &B32Config) -> Result<Self, std::io::Error> {
+        let encoder = B32Encoder::with_config(config)?;
+        Ok(B32EncoderWriter {
+            writer: Some(encoder.line_wrapper(writer)),
+            encoder,
+            write_buffer: vec![0; config.buffer_size / 5 * 8 + 8],
+            buffer_size: config.buffer_size,
+        })
+    }
 
-    // current output column (for wrapping)
-    let mut current_col: usize = 0;
+    ///
+    /// Encode any leftover bytes and return the wrapped writer
+    ///
+    pub fn finish(mut self) -> Result<W, std::io::Error> {
+        let mut lines = self.writer.take().unwrap();
+        self.encoder.finish(&mut lines)?;
+        lines.finish()
+    }
+}
 
-    loop {
-        // fill read buffer
-        let bytes_read = reader.read(&mut read_buffer[read_index..])?;
+impl<W: Write> Write for B32EncoderWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        // encode no more than fits in the write buffer
+        let n = buf.len().min(self.buffer_size);
+        let writer = self.writer.as_mut().unwrap();
+        self.encoder
+            .encode_buffer(&buf[0..n], &mut self.write_buffer, writer)?;
+        Ok(n)
+    }
 
-        // if out of data, exit loop
-        if bytes_read == 0 {
-            break;
-        }
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.as_mut().unwrap().flush()
+    }
+}
 
-        // update read_index base on bytes_read
-        read_index += bytes_read;
-
-        // process all chunks of 3 bytes into 4 output characters
-        for chunk in read_buffer[0..read_index].chunks_exact(5) {
-            let (a, b, c, d, e) = (chunk[0], chunk[1], chunk[2], chunk[3], chunk[4]);
-            write_buffer[write_index] = ALPHABET[(a >> 3) as usize];
-            write_buffer[write_index + 1] = ALPHABET[(((a & 0x7) << 2) | (b >> 6)) as usize];
-            write_buffer[write_index + 2] = ALPHABET[((b & 0x3F) >> 1) as usize];
-            write_buffer[write_index + 3] = ALPHABET[(((b & 0x1) << 4) | (c >> 4)) as usize];
-            write_buffer[write_index + 4] = ALPHABET[(((c & 0xF) << 1) | (d >> 7)) as usize];
-            write_buffer[write_index + 5] = ALPHABET[((d & 0x7F) >> 2) as usize];
-            write_buffer[write_index + 6] = ALPHABET[(((d & 0x3) << 3) | (e >> 5)) as usize];
-            write_buffer[write_index + 7] = ALPHABET[(e & 0x1F) as usize];
-            write_index += 8;
+impl<W: Write> Drop for B32EncoderWriter<W> {
+    fn drop(&mut self) {
+        // the final line is ended as the line wrapper is dropped
+        if let Some(mut lines) = self.writer.take() {
+            let _ = self.encoder.finish(&mut lines);
         }
+    }
+}
 
-        // move residual data to front of buffer
-        read_buffer.copy_within((read_index - (read_index % 5))..read_index, 0);
-
-        // update read index to end of residual data
-        read_index %= 5;
-
-        // output base64 characters
-        current_col = wrapping_write(&write_buffer, write_index, wrap, current_col, writer)?;
-        write_index = 0;
-    }
-
-    // process remaining data
-    match read_index % 5 {
-        0 => {}
-        1 => {
-            let a = read_buffer[0];
-            write_buffer[write_index] = ALPHABET[(a >> 3) as usize];
-            write_buffer[write_index + 1] = ALPHABET[((a & 0x7) << 2) as usize];
-            write_buffer[write_index + 2] = '=' as u8;
-            write_buffer[write_index + 3] = '=' as u8;
-            write_buffer[write_index + 4] = '=' as u8;
-            write_buffer[write_index + 5] = '=' as u8;
-            write_buffer[write_index + 6] = '=' as u8;
-            write_buffer[write_index + 7] = '=' as u8;
-            write_index += 8;
+///
+/// Incremental base-32 encoder
+///
+/// Data can be passed to `update` in fragments of any size, with the bytes left
+/// over from one fragment carried to the next.
+///
+pub struct B32Encoder {
+    // bytes left over from the previous buffer, too few to encode
+    remainder: [u8; 5],
+    remainder_len: usize,
+    // column at which to wrap encoded data
+    wrap: Option<usize>,
+    // current output column (for wrapping)
+    current_col: usize,
+    // whether or not to pad the final word
+    pad: bool,
+    alphabet: B32Alphabet,
+    line_ending: LineEnding,
+}
+
+impl B32Encoder {
+    ///
+    /// Create an encoder
+    ///
+    /// # Arguments
+    ///
+    /// * `wrap` - Column at which to wrap encoded data
+    ///
+    pub fn new(wrap: Option<usize>) -> Result<Self, std::io::Error> {
+        B32Encoder::with_config(&B32Config::STANDARD.wrap(wrap))
+    }
+
+    ///
+    /// Create an encoder with the given settings
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Encoding settings
+    ///
+    pub fn with_config(config: &B32Config) -> Result<Self, std::io::Error> {
+        // sanity-check parameters
+        if config.wrap == Some(0) {
+            return Err(std::io::Error::other("cannot wrap on column 0"));
         }
-        2 => {
-            let (a, b) = (read_buffer[0], read_buffer[1]);
-            write_buffer[write_index] = ALPHABET[(a >> 3) as usize];
-            write_buffer[write_index + 1] = ALPHABET[(((a & 0x7) << 2) | (b >> 6)) as usize];
-            write_buffer[write_index + 2] = ALPHABET[((b & 0x3F) >> 1) as usize];
-            write_buffer[write_index + 3] = ALPHABET[((b & 0x1) << 4) as usize];
-            write_buffer[write_index + 4] = '=' as u8;
-            write_buffer[write_index + 5] = '=' as u8;
-            write_buffer[write_index + 6] = '=' as u8;
-            write_buffer[write_index + 7] = '=' as u8;
-            write_index += 8;
+
+        Ok(B32Encoder {
+            remainder: [0; 5],
+            remainder_len: 0,
+            wrap: config.wrap,
+            current_col: 0,
+            pad: config.padding == Padding::Required,
+            alphabet: config.alphabet,
+            line_ending: config.line_ending,
+        })
+    }
+
+    ///
+    /// Encode a fragment of data
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - Fragment of data
+    /// * `output` - Vector to which encoded data will be appended
+    ///
+    pub fn update(&mut self, input: &[u8], output: &mut Vec<u8>) {
+        // the final line is ended by `finalize`
+        let mut lines = self.line_wrapper(output).final_newline(false);
+        self.encode_all(input, &mut lines)
+            .expect("writing to a Vec cannot fail");
+        self.current_col = lines.column();
+    }
+
+    ///
+    /// Encode any leftover bytes, with padding unless disabled
+    ///
+    /// # Arguments
+    ///
+    /// * `output` - Vector to which encoded data will be appended
+    ///
+    pub fn finalize(mut self, output: &mut Vec<u8>) {
+        let mut lines = self.line_wrapper(output);
+        self.finish(&mut lines)
+            .expect("writing to a Vec cannot fail");
+        lines.end().expect("writing to a Vec cannot fail");
+    }
+
+    // wrap a writer to break encoded data into lines, continuing from the current column
+    fn line_wrapper<W: Write>(&self, writer: W) -> LineWrapper<W> {
+        LineWrapper::new(writer, self.wrap)
+            .expect("wrap column checked when the encoder was created")
+            .separator(self.line_ending.as_bytes())
+            .final_newline(self.wrap.is_some())
+            .resume_at(self.current_col)
+    }
+
+    // encode input of any length and output all complete words
+    fn encode_all(
+        &mut self,
+        input: &[u8],
+        lines: &mut LineWrapper<impl Write>,
+    ) -> Result<(), std::io::Error> {
+        let mut write_buffer: [u8; 4096] = [0; 4096];
+        for chunk in input.chunks(2555) {
+            self.encode_buffer(chunk, &mut write_buffer, lines)?;
         }
-        3 => {
-            let (a, b, c) = (read_buffer[0], read_buffer[1], read_buffer[2]);
-            write_buffer[write_index] = ALPHABET[(a >> 3) as usize];
-            write_buffer[write_index + 1] = ALPHABET[(((a & 0x7) << 2) | (b >> 6)) as usize];
-            write_buffer[write_index + 2] = ALPHABET[((b & 0x3F) >> 1) as usize];
-            write_buffer[write_index + 3] = ALPHABET[(((b & 0x1) << 4) | (c >> 4)) as usize];
-            write_buffer[write_index + 4] = ALPHABET[((c & 0xF) << 1) as usize];
-            write_buffer[write_index + 5] = '=' as u8;
-            write_buffer[write_index + 6] = '=' as u8;
-            write_buffer[write_index + 7] = '=' as u8;
+        Ok(())
+    }
+
+    // encode a buffer of input and output all complete words, using `write_buffer`,
+    // which must have room for 8 chars for every 5 bytes of input, plus 8
+    fn encode_buffer(
+        &mut self,
+        buffer: &[u8],
+        write_buffer: &mut [u8],
+        lines: &mut LineWrapper<impl Write>,
+    ) -> Result<(), std::io::Error> {
+        let symbols = &self.alphabet.symbols;
+        let mut write_index: usize = 0;
+
+        // complete the leftover bytes first
+        let mut buffer = buffer;
+        if self.remainder_len > 0 {
+            let n = (5 - self.remainder_len).min(buffer.len());
+            self.remainder[self.remainder_len..self.remainder_len + n]
+                .copy_from_slice(&buffer[0..n]);
+            self.remainder_len += n;
+            buffer = &buffer[n..];
+            if self.remainder_len < 5 {
+                return Ok(());
+            }
+            encode_word(symbols, &self.remainder, &mut write_buffer[0..8]);
             write_index += 8;
+            self.remainder_len = 0;
         }
-        4 => {
-            let (a, b, c, d) = (
-                read_buffer[0],
-                read_buffer[1],
-                read_buffer[2],
-                read_buffer[3],
+
+        // process all chunks of 5 bytes into 8 output characters
+        let chunks = buffer.chunks_exact(5);
+        let remainder = chunks.remainder();
+        for chunk in chunks {
+            encode_word(
+                symbols,
+                chunk,
+                &mut write_buffer[write_index..write_index + 8],
             );
-            write_buffer[write_index] = ALPHABET[(a >> 3) as usize];
-            write_buffer[write_index + 1] = ALPHABET[(((a & 0x7) << 2) | (b >> 6)) as usize];
-            write_buffer[write_index + 2] = ALPHABET[((b & 0x3F) >> 1) as usize];
-            write_buffer[write_index + 3] = ALPHABET[(((b & 0x1) << 4) | (c >> 4)) as usize];
-            write_buffer[write_index + 4] = ALPHABET[(((c & 0xF) << 1) | (d >> 7)) as usize];
-            write_buffer[write_index + 5] = ALPHABET[((d & 0x7F) >> 2) as usize];
-            write_buffer[write_index + 6] = ALPHABET[((d & 0x3) << 3) as usize];
-            write_buffer[write_index + 7] = '=' as u8;
             write_index += 8;
         }
-        _ => {
-            unreachable!("impossible mod 3 value");
-        }
+
+        // keep leftover bytes for the next buffer
+        self.remainder[0..remainder.len()].copy_from_slice(remainder);
+        self.remainder_len = remainder.len();
+
+        // output base32 characters
+        lines.write_all(&write_buffer[0..write_index])?;
+
+        Ok(())
     }
 
-    // output base64 characters
-    let _ = wrapping_write(&write_buffer, write_index, wrap, current_col, writer)?;
+    // encode leftover bytes, with padding unless disabled, leaving the final line
+    // to be ended by the line wrapper
+    fn finish(&mut self, lines: &mut LineWrapper<impl Write>) -> Result<(), std::io::Error> {
+        if self.remainder_len > 0 {
+            // encode the leftover bytes followed by zeros, then replace the chars that
+            // only hold zero padding bits with padding chars
+            let mut word: [u8; 5] = [0; 5];
+            word[0..self.remainder_len].copy_from_slice(&self.remainder[0..self.remainder_len]);
+            let mut write_buffer: [u8; 8] = [0; 8];
+            encode_word(&self.alphabet.symbols, &word, &mut write_buffer);
+            let data_chars = match self.remainder_len {
+                1 => 2,
+                2 => 4,
+                3 => 5,
+                4 => 7,
+                _ => unreachable!("impossible mod 5 value"),
+            };
+            for c in write_buffer[data_chars..].iter_mut() {
+                *c = b'=';
+            }
 
-    // add a final newline, if wrapping is enabled
-    if wrap.is_some() {
-        writer.write_all(b"\n")?;
+            // output base32 characters, leaving off the padding if disabled
+            let len = if self.pad { 8 } else { data_chars };
+            lines.write_all(&write_buffer[0..len])?;
+            self.remainder_len = 0;
+        }
+
+        Ok(())
     }
+}
 
-    Ok(())
+// encode 5 bytes as 8 base-32 characters
+#[inline(always)]
+fn encode_word(symbols: &[u8; 32], chunk: &[u8], output: &mut [u8]) {
+    let (a, b, c, d, e) = (chunk[0], chunk[1], chunk[2], chunk[3], chunk[4]);
+    output[0] = symbols[(a >> 3) as usize];
+    output[1] = symbols[(((a & 0x7) << 2) | (b >> 6)) as usize];
+    output[2] = symbols[((b & 0x3F) >> 1) as usize];
+    output[3] = symbols[(((b & 0x1) << 4) | (c >> 4)) as usize];
+    output[4] = symbols[(((c & 0xF) << 1) | (d >> 7)) as usize];
+    output[5] = symbols[((d & 0x7F) >> 2) as usize];
+    output[6] = symbols[(((d & 0x3) << 3) | (e >> 5)) as usize];
+    output[7] = symbols[(e & 0x1F) as usize];
 }
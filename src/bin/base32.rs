@@ -27,6 +27,11 @@ fn app() -> Result<(), std::io::Error> {
                 .requires("decode")
                 .long("ignore-garbage")
                 .help("When decoding, ignore non-alphabet characters"))
+            .arg(Arg::with_name("concatenated")
+                .long("concatenated")
+                .requires("decode")
+                .conflicts_with_all(&["ignore_garbage", "dns", "proquint"])
+                .help("When decoding, continue past padding, as found where encoded files have been concatenated"))
             .arg(Arg::with_name("wrap")
                 .short("w")
                 .long("wrap")
@@ -34,18 +39,37 @@ fn app() -> Result<(), std::io::Error> {
                 .takes_value(true)
                 .default_value("76")
                 .validator(|arg| { arg.parse::<usize>().and(Ok(())).or(Err("wrap value must be a number".to_string())) }))
+            .arg(Arg::with_name("dns")
+                .long("dns")
+                .conflicts_with("ignore_garbage")
+                .help("Encode/decode data as a DNS-safe hostname of lowercase, unpadded labels"))
+            .arg(Arg::with_name("base32hex")
+                .long("base32hex")
+                .requires("dns")
+                .help("Use the extended hex alphabet for DNS-safe hostnames"))
+            .arg(Arg::with_name("proquint")
+                .long("proquint")
+                .conflicts_with_all(&["ignore_garbage", "dns"])
+                .help("Encode/decode data as pronounceable proquints"))
             .arg(Arg::with_name("FILE"))
             .get_matches();
 
     // pull out arguments
     let decode = matches.is_present("decode");
-    let ignore_garbage = matches.is_present("ignore_garbage");
+    let policy = if matches.is_present("concatenated") {
+        DecodePolicy::Concatenated
+    } else {
+        DecodePolicy::from(matches.is_present("ignore_garbage"))
+    };
     let wrap_column = matches
         .value_of("wrap")
         .unwrap()
         .parse::<usize>()
         .ok()
         .filter(|&x| x != 0);
+    let dns = matches.is_present("dns");
+    let extended_hex = matches.is_present("base32hex");
+    let proquint = matches.is_present("proquint");
     let file = matches.value_of("FILE").unwrap_or("-");
 
     // writer is always stdout
@@ -58,8 +82,23 @@ fn app() -> Result<(), std::io::Error> {
     let mut reader = get_reader(file, &stdin)?;
 
     // encode or decode as requested
-    if decode {
-        b32_decode(&mut reader, &mut writer, ignore_garbage)?;
+    if dns {
+        if decode {
+            b32_dns_decode(&mut reader, &mut writer, extended_hex)?;
+        } else {
+            b32_dns_encode(&mut reader, &mut writer, extended_hex)?;
+        }
+    } else if proquint {
+        if decode {
+            proquint_decode(&mut reader, &mut writer)?;
+        } else {
+            proquint_encode(&mut reader, &mut writer)?;
+        }
+    } else if decode {
+        // remember recent input to show decode errors in context
+        let mut reader = ContextReader::new(reader);
+        b32_decode(&mut reader, &mut writer, policy)
+            .map_err(|e| reader.diagnose(e, "base32"))?;
     } else {
         b32_encode(&mut reader, &mut writer, wrap_column)?;
     }
@@ -1,6 +1,6 @@
-use clap::{App, Arg};
+use clap::{App, Arg, ArgGroup};
 use std::fs::File;
-use std::io::{stdin, stdout, BufWriter, Read, Stdin};
+use std::io::{stdin, stdout, BufWriter, Read, Stdin, Write};
 
 use base_util::*;
 
@@ -27,6 +27,15 @@ fn app() -> Result<(), std::io::Error> {
                 .requires("decode")
                 .long("ignore-garbage")
                 .help("When decoding, ignore non-alphabet characters"))
+            .arg(Arg::with_name("concatenated")
+                .long("concatenated")
+                .requires("decode")
+                .conflicts_with_all(&["ignore_garbage", "mime_header", "ihex", "srec"])
+                .help("When decoding, continue past padding, as found where encoded files have been concatenated"))
+            .arg(Arg::with_name("mime")
+                .long("mime")
+                .conflicts_with_all(&["concatenated", "mime_header", "ihex", "srec"])
+                .help("Encode/decode data as an RFC 2045 MIME body part, with CRLF line endings, ignoring non-alphabet characters when decoding"))
             .arg(Arg::with_name("wrap")
                 .short("w")
                 .long("wrap")
@@ -34,20 +43,183 @@ fn app() -> Result<(), std::io::Error> {
                 .takes_value(true)
                 .default_value("76")
                 .validator(|arg| { arg.parse::<usize>().and(Ok(())).or(Err("wrap value must be a number".to_string())) }))
+            .arg(Arg::with_name("mime_header")
+                .long("mime-header")
+                .conflicts_with("ignore_garbage")
+                .help("Encode/decode data as RFC 2047 MIME encoded-words, for use in email headers"))
+            .arg(Arg::with_name("charset")
+                .long("charset")
+                .requires("mime_header")
+                .help("Charset named in MIME encoded-words (default UTF-8)")
+                .takes_value(true))
+            .arg(Arg::with_name("q_encoding")
+                .short("q")
+                .long("q-encoding")
+                .requires("mime_header")
+                .help("Use the Q encoding rather than the B encoding for MIME encoded-words"))
+            .arg(Arg::with_name("ihex")
+                .long("ihex")
+                .conflicts_with_all(&["ignore_garbage", "mime_header"])
+                .help("Convert data to/from Intel HEX records"))
+            .arg(Arg::with_name("srec")
+                .long("srec")
+                .conflicts_with_all(&["ignore_garbage", "mime_header"])
+                .help("Convert data to/from Motorola S-records"))
+            .arg(Arg::with_name("pem")
+                .long("pem")
+                .conflicts_with_all(&["ignore_garbage", "concatenated", "mime", "mime_header", "ihex", "srec", "hexdump"])
+                .help("Encode/decode data as RFC 7468 PEM blocks, such as certificates and keys"))
+            .arg(Arg::with_name("label")
+                .long("label")
+                .requires("pem")
+                .help("Label of the PEM block to encode (e.g. CERTIFICATE).  When decoding, output only blocks with this label")
+                .takes_value(true))
+            .arg(Arg::with_name("armor")
+                .long("armor")
+                .conflicts_with_all(&["ignore_garbage", "concatenated", "mime", "mime_header", "ihex", "srec", "hexdump", "pem"])
+                .help("Encode/decode data as RFC 4880 OpenPGP ASCII armor, verifying checksums when decoding"))
+            .arg(Arg::with_name("armor_type")
+                .long("armor-type")
+                .requires("armor")
+                .help("Type of armor block to encode (default message)")
+                .takes_value(true)
+                .possible_values(&["message", "public-key", "private-key", "signature"]))
+            .arg(Arg::with_name("comment")
+                .long("comment")
+                .requires("armor")
+                .help("Comment header of the armor block to encode")
+                .takes_value(true))
+            .arg(Arg::with_name("data_uri")
+                .long("data-uri")
+                .conflicts_with_all(&["ignore_garbage", "concatenated", "mime", "mime_header", "ihex", "srec", "hexdump", "pem", "armor"])
+                .help("Encode data as an unwrapped base64 data: URI, or decode any data: URI"))
+            .arg(Arg::with_name("media_type")
+                .long("media-type")
+                .requires("data_uri")
+                .help("Media type of the data URI to encode (default guessed from the data)")
+                .takes_value(true))
+            .arg(Arg::with_name("der")
+                .long("der")
+                .requires("decode")
+                .conflicts_with_all(&["mime_header", "ihex", "srec", "hexdump", "armor", "data_uri"])
+                .help("When decoding, print decoded data as an ASN.1 DER structure, as in certificates and keys"))
+            .arg(Arg::with_name("ssh_keys")
+                .long("ssh-keys")
+                .conflicts_with_all(&["decode", "mime", "mime_header", "ihex", "srec", "hexdump", "pem", "armor", "data_uri"])
+                .help("Print the size, fingerprint, comment and type of each SSH public key, as in authorized_keys and .pub files"))
+            .arg(Arg::with_name("md5")
+                .long("md5")
+                .requires("ssh_keys")
+                .help("Print MD5 rather than SHA256 fingerprints of SSH public keys"))
+            .arg(Arg::with_name("jwt")
+                .long("jwt")
+                .conflicts_with_all(&["decode", "mime", "mime_header", "ihex", "srec", "hexdump", "pem", "armor", "data_uri", "ssh_keys"])
+                .help("Print the segments of a JSON Web Token, pretty-printing JSON and flagging malformed segments"))
+            .group(ArgGroup::with_name("records")
+                .args(&["ihex", "srec"]))
+            .arg(Arg::with_name("base_address")
+                .long("base-address")
+                .requires("records")
+                .help("Address of the first byte of data converted to records (default 0)")
+                .takes_value(true)
+                .validator(|arg| { parse_address(&arg).and(Ok(())).or(Err("base address must be a 32-bit number".to_string())) }))
+            .arg(Arg::with_name("record_length")
+                .long("record-length")
+                .requires("records")
+                .help("Maximum number of data bytes per record (default 16)")
+                .takes_value(true)
+                .validator(|arg| { arg.parse::<usize>().and(Ok(())).or(Err("record length must be a number".to_string())) }))
+            .arg(Arg::with_name("hexdump")
+                .long("hexdump")
+                .conflicts_with_all(&["mime_header", "ihex", "srec"])
+                .help("When decoding, output decoded data as a hexdump.  When encoding, read input as a hexdump"))
+            .arg(Arg::with_name("plain")
+                .short("p")
+                .long("plain")
+                .requires("hexdump")
+                .help("Use plain hexdumps, as produced by xxd -p"))
+            .arg(Arg::with_name("columns")
+                .short("c")
+                .long("columns")
+                .requires("hexdump")
+                .help("Bytes per hexdump line (default 16, or 30 for plain hexdumps)")
+                .takes_value(true)
+                .validator(|arg| { arg.parse::<usize>().and(Ok(())).or(Err("columns value must be a number".to_string())) }))
+            .arg(Arg::with_name("group_size")
+                .short("g")
+                .long("group-size")
+                .requires("hexdump")
+                .help("Bytes per group in hexdump lines (default 2)")
+                .takes_value(true)
+                .validator(|arg| { arg.parse::<usize>().and(Ok(())).or(Err("group size must be a number".to_string())) }))
             .arg(Arg::with_name("FILE"))
             .get_matches();
 
     // pull out arguments
     let decode = matches.is_present("decode");
-    let ignore_garbage = matches.is_present("ignore_garbage");
+    let policy = if matches.is_present("concatenated") {
+        DecodePolicy::Concatenated
+    } else {
+        DecodePolicy::from(matches.is_present("ignore_garbage"))
+    };
+    let mime = matches.is_present("mime");
     let wrap_column = matches
         .value_of("wrap")
         .unwrap()
         .parse::<usize>()
         .ok()
         .filter(|&x| x != 0);
+    let mime_header = matches.is_present("mime_header");
+    let charset = matches.value_of("charset").unwrap_or("UTF-8");
+    let mime_encoding = if matches.is_present("q_encoding") {
+        MimeWordEncoding::Q
+    } else {
+        MimeWordEncoding::B
+    };
+    let ihex = matches.is_present("ihex");
+    let srec = matches.is_present("srec");
+    let pem = matches.is_present("pem");
+    let label = matches.value_of("label");
+    let armor = matches.is_present("armor");
+    let armor_kind = match matches.value_of("armor_type") {
+        Some("public-key") => ArmorKind::PublicKeyBlock,
+        Some("private-key") => ArmorKind::PrivateKeyBlock,
+        Some("signature") => ArmorKind::Signature,
+        _ => ArmorKind::Message,
+    };
+    let comment = matches.value_of("comment");
+    let data_uri = matches.is_present("data_uri");
+    let media_type = matches.value_of("media_type");
+    let der = matches.is_present("der");
+    let ssh_keys = matches.is_present("ssh_keys");
+    let md5 = matches.is_present("md5");
+    let jwt = matches.is_present("jwt");
+    let base_address = parse_address(matches.value_of("base_address").unwrap_or("0")).unwrap();
+    let record_length = matches
+        .value_of("record_length")
+        .unwrap_or("16")
+        .parse::<usize>()
+        .unwrap();
+    let hexdump = matches.is_present("hexdump");
+    let plain = matches.is_present("plain");
+    let columns = matches
+        .value_of("columns")
+        .map(|arg| arg.parse::<usize>().unwrap())
+        .unwrap_or(if plain { 30 } else { 16 });
+    let group_size = matches
+        .value_of("group_size")
+        .map(|arg| arg.parse::<usize>().unwrap())
+        .unwrap_or(2);
     let file = matches.value_of("FILE").unwrap_or("-");
 
+    // settings for plain base-64
+    let config = if mime {
+        B64Config::MIME
+    } else {
+        B64Config::STANDARD.decode_policy(policy)
+    }
+    .wrap(wrap_column);
+
     // writer is always stdout
     let stdout = stdout();
     let stdout_lock = stdout.lock();
@@ -58,10 +230,137 @@ fn app() -> Result<(), std::io::Error> {
     let mut reader = get_reader(file, &stdin)?;
 
     // encode or decode as requested
-    if decode {
-        b64_decode(&mut reader, &mut writer, ignore_garbage)?;
+    if mime_header {
+        if decode {
+            mime_header_decode(&mut reader, &mut writer)?;
+        } else {
+            mime_header_encode(&mut reader, &mut writer, charset, mime_encoding)?;
+        }
+    } else if ihex {
+        if decode {
+            ihex_decode(&mut reader, &mut writer)?;
+        } else {
+            ihex_encode(&mut reader, &mut writer, base_address, record_length)?;
+        }
+    } else if srec {
+        if decode {
+            srec_decode(&mut reader, &mut writer)?;
+        } else {
+            srec_encode(&mut reader, &mut writer, base_address, record_length)?;
+        }
+    } else if pem {
+        if decode {
+            // remember recent input to show decode errors in context
+            let mut reader = ContextReader::new(reader);
+            let blocks = pem_decode(&mut reader).map_err(|e| reader.diagnose(e, "base64"))?;
+            for block in blocks {
+                if label.is_some_and(|label| label != block.label) {
+                    continue;
+                }
+                if der {
+                    writeln!(writer, "{}:", block.label)?;
+                    der_dump(&block.data, &mut writer)?;
+                } else {
+                    writer.write_all(&block.data)?;
+                }
+            }
+        } else {
+            let label =
+                label.ok_or_else(|| std::io::Error::other("a label is required to encode PEM"))?;
+            pem_encode(&mut reader, &mut writer, label)?;
+        }
+    } else if armor {
+        if decode {
+            // remember recent input to show decode errors in context
+            let mut reader = ContextReader::new(reader);
+            let blocks = armor_decode(&mut reader).map_err(|e| reader.diagnose(e, "base64"))?;
+            for block in blocks {
+                writer.write_all(&block.data)?;
+            }
+        } else {
+            let mut data = Vec::new();
+            reader.read_to_end(&mut data)?;
+            let headers = comment
+                .map(|comment| vec![("Comment".to_string(), comment.to_string())])
+                .unwrap_or_default();
+            armor_encode_block(
+                &ArmorBlock {
+                    kind: armor_kind,
+                    headers,
+                    data,
+                },
+                &mut writer,
+            )?;
+        }
+    } else if data_uri {
+        if decode {
+            // remember recent input to show decode errors in context
+            let mut reader = ContextReader::new(reader);
+            data_uri_decode(&mut reader, &mut writer).map_err(|e| reader.diagnose(e, "base64"))?;
+        } else {
+            data_uri_encode(&mut reader, &mut writer, media_type)?;
+        }
+    } else if ssh_keys {
+        // print keys as ssh-keygen -l does
+        let mut reader = ContextReader::new(reader);
+        let keys = ssh_keys_parse(&mut reader).map_err(|e| reader.diagnose(e, "base64"))?;
+        for key in keys {
+            writeln!(
+                writer,
+                "{} {} {} ({})",
+                key.bits.map_or("?".to_string(), |bits| bits.to_string()),
+                if md5 {
+                    key.fingerprint_md5()
+                } else {
+                    key.fingerprint_sha256()
+                },
+                key.comment.as_deref().unwrap_or("no comment"),
+                key.algorithm()
+            )?;
+        }
+    } else if jwt {
+        // remember recent input to show decode errors in context
+        let mut reader = ContextReader::new(reader);
+        jwt_inspect(&mut reader, &mut writer).map_err(|e| reader.diagnose(e, "base64"))?;
+    } else if hexdump {
+        if decode {
+            // decode, then dump the decoded data
+            let mut decoded = Vec::new();
+            let mut reader = ContextReader::new(reader);
+            config
+                .decode(&mut reader, &mut decoded)
+                .map_err(|e| reader.diagnose(e, "base64"))?;
+            if plain {
+                hexdump_plain_encode(&mut decoded.as_slice(), &mut writer, columns)?;
+            } else {
+                hexdump_encode(&mut decoded.as_slice(), &mut writer, columns, group_size)?;
+            }
+        } else {
+            // parse the dump, then encode the parsed data
+            let mut data = Vec::new();
+            if plain {
+                hexdump_plain_decode(&mut reader, &mut data)?;
+            } else {
+                hexdump_decode(&mut reader, &mut data)?;
+            }
+            config.encode(&mut data.as_slice(), &mut writer)?;
+        }
+    } else if decode && der {
+        // decode, then dump the decoded structure
+        let mut decoded = Vec::new();
+        let mut reader = ContextReader::new(reader);
+        config
+            .decode(&mut reader, &mut decoded)
+            .map_err(|e| reader.diagnose(e, "base64"))?;
+        der_dump(&decoded, &mut writer)?;
+    } else if decode {
+        // remember recent input to show decode errors in context
+        let mut reader = ContextReader::new(reader);
+        config
+            .decode(&mut reader, &mut writer)
+            .map_err(|e| reader.diagnose(e, "base64"))?;
     } else {
-        b64_encode(&mut reader, &mut writer, wrap_column)?;
+        config.encode(&mut reader, &mut writer)?;
     }
 
     Ok(())
@@ -75,3 +374,10 @@ fn get_reader<'a>(file: &str, stdin: &'a Stdin) -> Result<Box<dyn Read + 'a>, st
         Ok(Box::new(file))
     }
 }
+
+fn parse_address(arg: &str) -> Result<u32, std::num::ParseIntError> {
+    match arg.strip_prefix("0x").or_else(|| arg.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => arg.parse::<u32>(),
+    }
+}
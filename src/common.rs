@@ -1,47 +1,84 @@
 use std::io::Write;
 
-pub fn wrapping_write(
-    buffer: &[u8],
-    len: usize,
-    wrap_col: Option<usize>,
-    mut current_col: usize,
-    writer: &mut impl Write,
-) -> Result<usize, std::io::Error> {
-    // if wrapping is required
-    if let Some(line_length) = wrap_col {
-        let mut written: usize = 0;
-
-        // while there are more bytes to write
-        while written < len {
-            // calculate bytes remaining in line and total bytes remaining
-            let line_remaining: usize = line_length - current_col;
-            let byte_remaining: usize = len - written;
-
-            // bytes to write this iteration is the min of those values
-            let n: usize = line_remaining.min(byte_remaining);
-
-            // write the output
-            writer.write_all(&buffer[written..written + n])?;
-            written += n;
-
-            // if we wrote all remaining bytes on this line
-            if n == byte_remaining {
-                // advance to the new column
-                current_col += n;
-                break;
-            // otherwise
-            } else {
-                // add a newline and reset the column counter
-                writer.write_all(b"\n")?;
-                current_col = 0 as usize;
-            }
+// upper-case hex digits
+pub(crate) const HEX_DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+
+// lower-case hex digits
+pub(crate) const LOWER_HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+// append a byte to a buffer as two hex digits from `digits`
+pub(crate) fn push_hex_byte(buffer: &mut Vec<u8>, b: u8, digits: &[u8; 16]) {
+    buffer.push(digits[(b >> 4) as usize]);
+    buffer.push(digits[(b & 0xF) as usize]);
+}
+
+// format bytes as hex digits from `digits`, with `separator` between each byte
+pub(crate) fn hex_string(bytes: &[u8], digits: &[u8; 16], separator: &str) -> String {
+    let mut buffer = Vec::with_capacity(bytes.len() * (2 + separator.len()));
+    for (i, &b) in bytes.iter().enumerate() {
+        if i > 0 {
+            buffer.extend_from_slice(separator.as_bytes());
         }
-    // otherwise, if no wrapping
-    } else {
-        // just output all the data
-        writer.write_all(&buffer[0..len])?;
+        push_hex_byte(&mut buffer, b, digits);
+    }
+    String::from_utf8(buffer).expect("hex is ASCII")
+}
+
+// value of a hex digit, if valid
+pub(crate) fn hex_value(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        b'A'..=b'F' => Some(c - b'A' + 10),
+        _ => None,
+    }
+}
+
+// parse a string of hex digit pairs into bytes
+pub(crate) fn parse_hex_bytes(text: &[u8]) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    text.chunks_exact(2)
+        .map(|pair| Some((hex_value(pair[0])? << 4) | hex_value(pair[1])?))
+        .collect()
+}
+
+// largest memory image that will be assembled, as sparse records could
+// otherwise span the whole address space
+const MAX_IMAGE_SIZE: u64 = 64 * 1024 * 1024;
+
+// write a memory image assembled from (address, data) segments, starting at the
+// lowest address and filling any gaps with 0xFF (the erased state of flash)
+pub(crate) fn write_image(
+    segments: Vec<(u64, Vec<u8>)>,
+    writer: &mut impl Write,
+) -> Result<(), std::io::Error> {
+    // find the extent of the image
+    let start = match segments.iter().map(|(address, _)| *address).min() {
+        Some(address) => address,
+        None => return Ok(()),
+    };
+    let end = segments
+        .iter()
+        .map(|(address, data)| address + data.len() as u64)
+        .max()
+        .unwrap();
+    if end - start > MAX_IMAGE_SIZE {
+        return Err(std::io::Error::other(format!(
+            "image from {:#010X} to {:#010X} is too large to fill gaps in (limit is {} MiB)",
+            start,
+            end,
+            MAX_IMAGE_SIZE / (1024 * 1024)
+        )));
+    }
+
+    // lay out segments in the image, later records overwriting earlier ones
+    let mut image = vec![0xFF; (end - start) as usize];
+    for (address, data) in segments {
+        let offset = (address - start) as usize;
+        image[offset..offset + data.len()].copy_from_slice(&data);
     }
 
-    // return the new column
-    Ok(current_col)
+    writer.write_all(&image)
 }
@@ -0,0 +1,241 @@
+use crate::alphabet::Alphabet;
+
+///
+/// Whether padding is expected at the end of encoded data
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Padding {
+    /// The final word must be padded to full length
+    #[default]
+    Required,
+    /// The final word may be padded or unpadded
+    Indifferent,
+    /// The final word must not be padded
+    Forbidden,
+}
+
+///
+/// How a decoder treats whitespace, invalid characters and padded words
+///
+/// A `bool` converts to `IgnoreGarbage` if true, or `WhitespaceTolerant` if
+/// false, matching the `ignore_garbage` flag this replaces.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DecodePolicy {
+    /// Whitespace and invalid characters are errors
+    NoWhitespace,
+    /// Whitespace is skipped, and invalid characters are errors
+    #[default]
+    WhitespaceTolerant,
+    /// Whitespace and invalid characters are skipped, as is everything after
+    /// the first padded word
+    IgnoreGarbage,
+    /// Whitespace is skipped, and decoding stops after the first padded word,
+    /// leaving the rest of the input unconsumed
+    StopAtPadding,
+    /// Whitespace is skipped, and each padded word ends a segment, with
+    /// decoding continuing into the next
+    Concatenated,
+}
+
+impl From<bool> for DecodePolicy {
+    fn from(ignore_garbage: bool) -> Self {
+        if ignore_garbage {
+            DecodePolicy::IgnoreGarbage
+        } else {
+            DecodePolicy::WhitespaceTolerant
+        }
+    }
+}
+
+///
+/// Line ending used when wrapping encoded data
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineEnding {
+    /// `\n`
+    #[default]
+    Lf,
+    /// `\r\n`, as used by MIME and other network protocols
+    CrLf,
+}
+
+impl LineEnding {
+    ///
+    /// Bytes that end a line
+    ///
+    pub const fn as_bytes(self) -> &'static [u8] {
+        match self {
+            LineEnding::Lf => b"\n",
+            LineEnding::CrLf => b"\r\n",
+        }
+    }
+}
+
+///
+/// Encoding and decoding settings, to be set once and reused
+///
+/// Setters are `const fn`s, so profiles can be declared as constants, and
+/// the codec modules implement encoding and decoding methods for them.
+/// When encoding, the final word is padded only if padding is
+/// `Padding::Required`.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Config<const N: usize> {
+    pub(crate) alphabet: Alphabet<N>,
+    pub(crate) padding: Padding,
+    pub(crate) strict: bool,
+    pub(crate) wrap: Option<usize>,
+    pub(crate) line_ending: LineEnding,
+    pub(crate) policy: DecodePolicy,
+    pub(crate) buffer_size: usize,
+}
+
+/// Settings for base-64
+pub type B64Config = Config<64>;
+
+/// Settings for base-32
+pub type B32Config = Config<32>;
+
+impl<const N: usize> Config<N> {
+    ///
+    /// Create settings with an alphabet, and defaults for everything else
+    ///
+    /// By default, encoded data is padded and not wrapped, and whitespace is
+    /// skipped when decoding.
+    ///
+    /// # Arguments
+    ///
+    /// * `alphabet` - Symbols used to encode data
+    ///
+    pub const fn new(alphabet: Alphabet<N>) -> Self {
+        Config {
+            alphabet,
+            padding: Padding::Required,
+            strict: false,
+            wrap: None,
+            line_ending: LineEnding::Lf,
+            policy: DecodePolicy::WhitespaceTolerant,
+            buffer_size: 65536,
+        }
+    }
+
+    ///
+    /// Set the symbols used to encode data
+    ///
+    /// # Arguments
+    ///
+    /// * `alphabet` - Alphabet
+    ///
+    pub const fn alphabet(mut self, alphabet: Alphabet<N>) -> Self {
+        self.alphabet = alphabet;
+        self
+    }
+
+    ///
+    /// Set whether the final word is padded
+    ///
+    /// # Arguments
+    ///
+    /// * `padding` - Padding policy (`Padding::Required` by default)
+    ///
+    pub const fn padding(mut self, padding: Padding) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    ///
+    /// Set whether decoding rejects non-zero bits in the final character that
+    /// would be discarded
+    ///
+    /// # Arguments
+    ///
+    /// * `strict` - Whether or not to reject non-canonical encodings (off by default)
+    ///
+    pub const fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    ///
+    /// Set the column at which to wrap encoded data
+    ///
+    /// Encoding fails if this is column 0.
+    ///
+    /// # Arguments
+    ///
+    /// * `wrap` - Column at which to wrap encoded data (not wrapped by default)
+    ///
+    pub const fn wrap(mut self, wrap: Option<usize>) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    ///
+    /// Set the line ending used when wrapping encoded data
+    ///
+    /// # Arguments
+    ///
+    /// * `line_ending` - Line ending (`LineEnding::Lf` by default)
+    ///
+    pub const fn line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    ///
+    /// Set how decoding treats whitespace, invalid characters and padded words
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - Decoding policy (`DecodePolicy::WhitespaceTolerant` by default)
+    ///
+    pub const fn decode_policy(mut self, policy: DecodePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    ///
+    /// Set the size of the buffers used when encoding and decoding streams
+    ///
+    /// Panics if `buffer_size` is 0.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer_size` - Bytes of input read at a time (65536 by default)
+    ///
+    pub const fn buffer_size(mut self, buffer_size: usize) -> Self {
+        assert!(buffer_size > 0, "buffer size cannot be 0");
+        self.buffer_size = buffer_size;
+        self
+    }
+}
+
+impl Config<64> {
+    /// The standard base-64 alphabet, padded
+    pub const STANDARD: Self = Config::new(Alphabet::<64>::STANDARD);
+
+    /// The URL and filename safe base-64 alphabet, unpadded, but accepting
+    /// padding when decoding
+    pub const URL_SAFE: Self = Config::new(Alphabet::<64>::URL_SAFE).padding(Padding::Indifferent);
+
+    /// The standard base-64 alphabet as used by MIME (RFC 2045, section 6.8),
+    /// padded and wrapped at 76 columns with CRLF line endings, and ignoring
+    /// characters outside the alphabet when decoding
+    pub const MIME: Self = Config::new(Alphabet::<64>::STANDARD)
+        .wrap(Some(76))
+        .line_ending(LineEnding::CrLf)
+        .decode_policy(DecodePolicy::IgnoreGarbage);
+
+    /// The standard base-64 alphabet as used by PEM (RFC 7468, section 2),
+    /// padded and wrapped at 64 columns
+    pub const PEM: Self = Self::STANDARD.wrap(Some(64));
+}
+
+impl Config<32> {
+    /// The standard base-32 alphabet, padded
+    pub const STANDARD: Self = Config::new(Alphabet::<32>::STANDARD);
+
+    /// The "extended hex" base-32 alphabet, padded
+    pub const EXTENDED_HEX: Self = Config::new(Alphabet::<32>::EXTENDED_HEX);
+}
@@ -0,0 +1,231 @@
+use std::borrow::Cow;
+use std::io::{Read, Write};
+
+use crate::common::hex_value;
+use crate::config::B64Config;
+use crate::error::{relocate_error, Position};
+
+// media type assumed when a data URI omits one (RFC 2397, section 2)
+const DEFAULT_MEDIA_TYPE: &str = "text/plain;charset=US-ASCII";
+
+///
+/// A parsed data URI
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataUri {
+    /// Media type, including any parameters (e.g. "text/plain;charset=UTF-8")
+    pub media_type: String,
+    /// Whether or not the data was base-64 encoded
+    pub base64: bool,
+    /// Decoded data
+    pub data: Vec<u8>,
+}
+
+///
+/// Guess the media type of data from its first few bytes
+///
+/// Recognizes PNG, JPEG, GIF, PDF, WebP, SVG and JSON.
+///
+/// # Arguments
+///
+/// * `data` - Data to identify
+///
+pub fn sniff_media_type(data: &[u8]) -> Option<&'static str> {
+    // binary formats have magic numbers
+    if data.starts_with(b"\x89PNG\r\n\x1A\n") {
+        return Some("image/png");
+    }
+    if data.starts_with(b"\xFF\xD8\xFF") {
+        return Some("image/jpeg");
+    }
+    if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        return Some("image/gif");
+    }
+    if data.starts_with(b"%PDF-") {
+        return Some("application/pdf");
+    }
+    if data.len() >= 12 && data.starts_with(b"RIFF") && &data[8..12] == b"WEBP" {
+        return Some("image/webp");
+    }
+
+    // text formats are recognized by their first and last characters
+    let text = data
+        .strip_prefix(b"\xEF\xBB\xBF")
+        .unwrap_or(data)
+        .trim_ascii();
+    if text.starts_with(b"<svg") || (text.starts_with(b"<?xml") && contains(text, b"<svg")) {
+        return Some("image/svg+xml");
+    }
+    if std::str::from_utf8(text).is_ok()
+        && ((text.starts_with(b"{") && text.ends_with(b"}"))
+            || (text.starts_with(b"[") && text.ends_with(b"]")))
+    {
+        return Some("application/json");
+    }
+
+    None
+}
+
+///
+/// Encode data as a base-64 data URI
+///
+/// The URI is written unwrapped, without a trailing newline.
+///
+/// # Arguments
+///
+/// * `reader` - Data to encode
+/// * `writer` - Writer to which the data URI will be written
+/// * `media_type` - Media type of the data, or `None` to guess it, falling
+///   back to "application/octet-stream"
+///
+pub fn data_uri_encode(
+    reader: &mut impl Read,
+    writer: &mut impl Write,
+    media_type: Option<&str>,
+) -> Result<(), std::io::Error> {
+    // JSON is recognized by its last character and by being valid UTF-8, so guessing
+    // the media type needs the whole data, which is then replayed for encoding
+    let mut data = Vec::new();
+    let media_type = match media_type {
+        Some(media_type) => media_type,
+        None => {
+            reader.read_to_end(&mut data)?;
+            sniff_media_type(&data).unwrap_or("application/octet-stream")
+        }
+    };
+
+    // sanity-check parameters, so the output can be parsed again
+    if !media_type
+        .bytes()
+        .all(|c| c.is_ascii_graphic() && c != b',')
+    {
+        return Err(std::io::Error::other("invalid media type"));
+    }
+
+    write!(writer, "data:{};base64,", media_type)?;
+    B64Config::STANDARD.encode(&mut data.as_slice().chain(reader), writer)
+}
+
+///
+/// Decode a data URI
+///
+/// # Arguments
+///
+/// * `reader` - Data URI reader
+/// * `writer` - Writer to which the decoded data will be written
+///
+pub fn data_uri_decode(
+    reader: &mut impl Read,
+    writer: &mut impl Write,
+) -> Result<(), std::io::Error> {
+    let mut input = Vec::new();
+    reader.read_to_end(&mut input)?;
+    writer.write_all(&data_uri_parse(&input)?.data)
+}
+
+///
+/// Parse a data URI, base-64 or percent-encoded
+///
+/// Errors in base-64 data are reported as a `DecodeError` located within the
+/// URI, unless the data was also percent-encoded.
+///
+/// # Arguments
+///
+/// * `uri` - Data URI, with optional surrounding whitespace
+///
+pub fn data_uri_parse(uri: &[u8]) -> Result<DataUri, std::io::Error> {
+    let invalid = |reason: &str| std::io::Error::other(format!("invalid data URI: {}", reason));
+
+    // leading whitespace must be accounted for when locating errors
+    let leading = &uri[0..uri.len() - uri.trim_ascii_start().len()];
+    let uri = uri.trim_ascii();
+
+    // split "data:<media type>[;base64],<data>"
+    if uri.len() < 5 || !uri[0..5].eq_ignore_ascii_case(b"data:") {
+        return Err(invalid("missing \"data:\" scheme"));
+    }
+    let comma = uri
+        .iter()
+        .position(|&c| c == b',')
+        .ok_or_else(|| invalid("missing ','"))?;
+    let header = &uri[5..comma];
+    let payload = &uri[comma + 1..];
+
+    let (media_type, base64) = match header.len().checked_sub(7) {
+        Some(n) if header[n..].eq_ignore_ascii_case(b";base64") => (&header[0..n], true),
+        _ => (header, false),
+    };
+    let media_type = match percent_decode(media_type).map_err(|reason| invalid(&reason))? {
+        media_type if media_type.is_empty() => DEFAULT_MEDIA_TYPE.to_string(),
+        // a bare charset parameter implies text/plain
+        media_type if media_type.starts_with(b";") => {
+            format!("text/plain{}", String::from_utf8_lossy(&media_type))
+        }
+        media_type => String::from_utf8_lossy(&media_type).into_owned(),
+    };
+
+    // the data is percent-encoded, and may also be base-64 encoded
+    let decoded = percent_decode(payload).map_err(|reason| invalid(&reason))?;
+    let data = if base64 {
+        // errors can only be located if the payload is on the first line, and
+        // wasn't percent-encoded
+        let offset = leading.len() + comma + 1;
+        let payload_start = Position {
+            offset,
+            line: 1,
+            column: offset + 1,
+        };
+        let located = matches!(decoded, Cow::Borrowed(_))
+            && !leading.contains(&b'\n')
+            && !header.contains(&b'\n');
+        B64Config::STANDARD
+            .decode_to_vec(&decoded)
+            .map_err(|error| relocate_error(error, located.then_some(payload_start)))?
+    } else {
+        decoded.into_owned()
+    };
+
+    Ok(DataUri {
+        media_type,
+        base64,
+        data,
+    })
+}
+
+// undo percent-encoding, borrowing the input if there is none
+fn percent_decode(input: &[u8]) -> Result<Cow<'_, [u8]>, String> {
+    if !input.contains(&b'%') {
+        return Ok(Cow::Borrowed(input));
+    }
+
+    let mut output = Vec::with_capacity(input.len());
+    let mut index = 0;
+    while index < input.len() {
+        if input[index] == b'%' {
+            let hex = &input[index + 1..(index + 3).min(input.len())];
+            let value = match *hex {
+                [high, low] => hex_value(high).zip(hex_value(low)),
+                _ => None,
+            };
+            let Some((high, low)) = value else {
+                return Err(format!(
+                    "invalid percent-escape \"%{}\"",
+                    String::from_utf8_lossy(hex)
+                ));
+            };
+            output.push(high << 4 | low);
+            index += 3;
+        } else {
+            output.push(input[index]);
+            index += 1;
+        }
+    }
+    Ok(Cow::Owned(output))
+}
+
+// whether or not `needle` appears in `haystack`
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack
+        .windows(needle.len())
+        .any(|window| window == needle)
+}
@@ -0,0 +1,332 @@
+use std::io::Write;
+
+use crate::common::{hex_string, HEX_DIGITS};
+
+// nesting beyond this is almost certainly not a real structure
+const MAX_DEPTH: usize = 64;
+
+// names of common object identifiers, mostly from certificates and keys
+const OID_NAMES: &[(&str, &str)] = &[
+    ("1.2.840.113549.1.1.1", "rsaEncryption"),
+    ("1.2.840.113549.1.1.5", "sha1WithRSAEncryption"),
+    ("1.2.840.113549.1.1.10", "rsassaPss"),
+    ("1.2.840.113549.1.1.11", "sha256WithRSAEncryption"),
+    ("1.2.840.113549.1.1.12", "sha384WithRSAEncryption"),
+    ("1.2.840.113549.1.1.13", "sha512WithRSAEncryption"),
+    ("1.2.840.113549.1.9.1", "emailAddress"),
+    ("1.2.840.113549.1.9.14", "extensionRequest"),
+    ("1.2.840.10045.2.1", "ecPublicKey"),
+    ("1.2.840.10045.3.1.7", "prime256v1"),
+    ("1.2.840.10045.4.3.2", "ecdsa-with-SHA256"),
+    ("1.2.840.10045.4.3.3", "ecdsa-with-SHA384"),
+    ("1.2.840.10045.4.3.4", "ecdsa-with-SHA512"),
+    ("1.3.132.0.34", "secp384r1"),
+    ("1.3.132.0.35", "secp521r1"),
+    ("1.3.101.110", "X25519"),
+    ("1.3.101.112", "Ed25519"),
+    ("1.3.14.3.2.26", "sha1"),
+    ("2.16.840.1.101.3.4.2.1", "sha256"),
+    ("2.16.840.1.101.3.4.2.2", "sha384"),
+    ("2.16.840.1.101.3.4.2.3", "sha512"),
+    ("2.5.4.3", "commonName"),
+    ("2.5.4.5", "serialNumber"),
+    ("2.5.4.6", "countryName"),
+    ("2.5.4.7", "localityName"),
+    ("2.5.4.8", "stateOrProvinceName"),
+    ("2.5.4.10", "organizationName"),
+    ("2.5.4.11", "organizationalUnitName"),
+    ("2.5.29.14", "subjectKeyIdentifier"),
+    ("2.5.29.15", "keyUsage"),
+    ("2.5.29.17", "subjectAltName"),
+    ("2.5.29.19", "basicConstraints"),
+    ("2.5.29.31", "cRLDistributionPoints"),
+    ("2.5.29.32", "certificatePolicies"),
+    ("2.5.29.35", "authorityKeyIdentifier"),
+    ("2.5.29.37", "extKeyUsage"),
+    ("1.3.6.1.5.5.7.1.1", "authorityInfoAccess"),
+    ("1.3.6.1.5.5.7.3.1", "serverAuth"),
+    ("1.3.6.1.5.5.7.3.2", "clientAuth"),
+    ("1.3.6.1.5.5.7.3.3", "codeSigning"),
+    ("1.3.6.1.5.5.7.48.1", "ocsp"),
+    ("1.3.6.1.5.5.7.48.2", "caIssuers"),
+];
+
+///
+/// Print DER-encoded data as an indented tree
+///
+/// Each element is printed on its own line, with its offset, tag and length,
+/// followed by its value if primitive, or its children if constructed.  Bit
+/// and octet strings holding DER, as in keys and certificate extensions, are
+/// printed as constructed.
+///
+/// # Arguments
+///
+/// * `data` - DER-encoded data
+/// * `writer` - Writer to which the tree will be written
+///
+pub fn der_dump(data: &[u8], writer: &mut impl Write) -> Result<(), std::io::Error> {
+    let mut output = Vec::new();
+    let result = dump_elements(data, 0, 0, &mut output);
+
+    // print as much as could be parsed, even if it ends with an error
+    writer.write_all(&output)?;
+    result.map_err(|(offset, reason)| {
+        std::io::Error::other(format!("invalid DER at offset {}: {}", offset, reason))
+    })
+}
+
+///
+/// Name of a common object identifier, if known
+///
+/// # Arguments
+///
+/// * `oid` - Object identifier, in dotted form (e.g. "2.5.4.3")
+///
+pub fn oid_name(oid: &str) -> Option<&'static str> {
+    OID_NAMES
+        .iter()
+        .find(|(known, _)| *known == oid)
+        .map(|(_, name)| *name)
+}
+
+// an element's identifier, and its content
+struct Element<'a> {
+    class: u8,
+    constructed: bool,
+    tag: u64,
+    header_len: usize,
+    content: &'a [u8],
+}
+
+// an error's offset in the whole input, and reason
+type DumpError = (usize, &'static str);
+
+// print each element in `data`, which is at `offset` in the whole input
+fn dump_elements(
+    data: &[u8],
+    offset: usize,
+    depth: usize,
+    output: &mut Vec<u8>,
+) -> Result<(), DumpError> {
+    if depth > MAX_DEPTH {
+        return Err((offset, "nested too deeply"));
+    }
+
+    let mut index = 0;
+    while index < data.len() {
+        let element = parse_element(&data[index..]).map_err(|reason| (offset + index, reason))?;
+        let element_offset = offset + index;
+        let content_offset = element_offset + element.header_len;
+        index += element.header_len + element.content.len();
+
+        let len = element.content.len();
+        write!(
+            output,
+            "{:>6}: {:indent$}{}, {} byte{}",
+            element_offset,
+            "",
+            tag_name(&element),
+            len,
+            if len == 1 { "" } else { "s" },
+            indent = depth * 2
+        )
+        .unwrap();
+
+        if element.constructed {
+            output.push(b'\n');
+            dump_elements(element.content, content_offset, depth + 1, output)?;
+            continue;
+        }
+
+        // strings may hold DER, in which case print it as children
+        let encapsulated = match (element.class, element.tag) {
+            (0, 3) if element.content.first() == Some(&0) => Some(1),
+            (0, 4) => Some(0),
+            _ => None,
+        };
+        if let Some(skip) = encapsulated {
+            let inner = &element.content[skip..];
+            let mut children = Vec::new();
+            if matches!(inner.first(), Some(0x30) | Some(0x31))
+                && dump_elements(inner, content_offset + skip, depth + 1, &mut children).is_ok()
+            {
+                output.push(b'\n');
+                output.extend_from_slice(&children);
+                continue;
+            }
+        }
+
+        match value(&element) {
+            value if value.is_empty() => writeln!(output),
+            value => writeln!(output, ": {}", value),
+        }
+        .unwrap();
+    }
+
+    Ok(())
+}
+
+// parse the element at the start of `data`
+fn parse_element(data: &[u8]) -> Result<Element<'_>, &'static str> {
+    let mut bytes = data.iter().copied();
+    let identifier = bytes.next().ok_or("truncated element")?;
+
+    // tags over 30 continue in base-128
+    let mut tag = (identifier & 0x1F) as u64;
+    if tag == 0x1F {
+        tag = 0;
+        loop {
+            let byte = bytes.next().ok_or("truncated tag")?;
+            if tag >> 56 != 0 {
+                return Err("tag too large");
+            }
+            tag = (tag << 7) | (byte & 0x7F) as u64;
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+    }
+
+    // lengths over 127 are given as a count of big-endian bytes
+    let first = bytes.next().ok_or("truncated length")?;
+    let len = match first {
+        0x80 => return Err("indefinite length"),
+        0x00..=0x7F => first as usize,
+        _ => {
+            let count = (first & 0x7F) as usize;
+            if count > std::mem::size_of::<usize>() {
+                return Err("length too large");
+            }
+            let mut len = 0;
+            for _ in 0..count {
+                len = (len << 8) | bytes.next().ok_or("truncated length")? as usize;
+            }
+            len
+        }
+    };
+
+    let header_len = data.len() - bytes.len();
+    if len > data.len() - header_len {
+        return Err("truncated content");
+    }
+    Ok(Element {
+        class: identifier >> 6,
+        constructed: identifier & 0x20 != 0,
+        tag,
+        header_len,
+        content: &data[header_len..header_len + len],
+    })
+}
+
+// name of an element's tag
+fn tag_name(element: &Element) -> String {
+    let name = match (element.class, element.tag) {
+        (0, 1) => "BOOLEAN",
+        (0, 2) => "INTEGER",
+        (0, 3) => "BIT STRING",
+        (0, 4) => "OCTET STRING",
+        (0, 5) => "NULL",
+        (0, 6) => "OBJECT IDENTIFIER",
+        (0, 10) => "ENUMERATED",
+        (0, 12) => "UTF8String",
+        (0, 16) => "SEQUENCE",
+        (0, 17) => "SET",
+        (0, 19) => "PrintableString",
+        (0, 20) => "T61String",
+        (0, 22) => "IA5String",
+        (0, 23) => "UTCTime",
+        (0, 24) => "GeneralizedTime",
+        (0, 26) => "VisibleString",
+        (0, 28) => "UniversalString",
+        (0, 30) => "BMPString",
+        (0, tag) => return format!("[UNIVERSAL {}]", tag),
+        (1, tag) => return format!("[APPLICATION {}]", tag),
+        (2, tag) => return format!("[{}]", tag),
+        (_, tag) => return format!("[PRIVATE {}]", tag),
+    };
+    name.to_string()
+}
+
+// printable value of a primitive element, falling back to hex
+fn value(element: &Element) -> String {
+    let content = element.content;
+    let text = match (element.class, element.tag) {
+        (0, 1) if content.len() == 1 => Some((content[0] != 0).to_string()),
+        (0, 2) | (0, 10) if !content.is_empty() && content.len() <= 16 => {
+            // sign-extend big-endian two's complement
+            let fill = if content[0] & 0x80 != 0 { -1 } else { 0 };
+            Some(
+                content
+                    .iter()
+                    .fold(fill as i128, |n, &byte| (n << 8) | byte as i128)
+                    .to_string(),
+            )
+        }
+        (0, 3) if !content.is_empty() => Some(match content[0] {
+            0 => hex_string(&content[1..], HEX_DIGITS, ""),
+            unused => format!(
+                "{} ({} unused bits)",
+                hex_string(&content[1..], HEX_DIGITS, ""),
+                unused
+            ),
+        }),
+        (0, 5) if content.is_empty() => Some(String::new()),
+        (0, 6) => oid(content).map(|oid| match oid_name(&oid) {
+            Some(name) => format!("{} ({})", oid, name),
+            None => oid,
+        }),
+        (0, 12) | (0, 19) | (0, 20) | (0, 22) | (0, 26) => {
+            Some(format!("{:?}", String::from_utf8_lossy(content)))
+        }
+        (0, 23) | (0, 24) => Some(String::from_utf8_lossy(content).into_owned()),
+        (0, 28) if content.len().is_multiple_of(4) => Some(format!(
+            "{:?}",
+            content
+                .chunks_exact(4)
+                .map(
+                    |c| char::from_u32(u32::from_be_bytes(c.try_into().unwrap()))
+                        .unwrap_or(char::REPLACEMENT_CHARACTER)
+                )
+                .collect::<String>()
+        )),
+        (0, 30) if content.len().is_multiple_of(2) => Some(format!(
+            "{:?}",
+            String::from_utf16_lossy(
+                &content
+                    .chunks_exact(2)
+                    .map(|c| u16::from_be_bytes([c[0], c[1]]))
+                    .collect::<Vec<u16>>()
+            )
+        )),
+        _ => None,
+    };
+    text.unwrap_or_else(|| hex_string(content, HEX_DIGITS, ""))
+}
+
+// dotted form of an object identifier
+fn oid(content: &[u8]) -> Option<String> {
+    // each arc is base-128, with the first two packed into one
+    let mut arcs = Vec::new();
+    let mut arc: u128 = 0;
+    for (index, &byte) in content.iter().enumerate() {
+        if arc >> 121 != 0 {
+            return None;
+        }
+        arc = (arc << 7) | (byte & 0x7F) as u128;
+        if byte & 0x80 != 0 {
+            if index == content.len() - 1 {
+                return None;
+            }
+            continue;
+        }
+        if arcs.is_empty() {
+            let first = (arc / 40).min(2);
+            arcs.push(first.to_string());
+            arcs.push((arc - first * 40).to_string());
+        } else {
+            arcs.push(arc.to_string());
+        }
+        arc = 0;
+    }
+    (!arcs.is_empty()).then(|| arcs.join("."))
+}
@@ -0,0 +1,149 @@
+use std::io::{ErrorKind, Read};
+
+use crate::error::DecodeError;
+
+// bytes of earlier input retained for showing the start of the offending line
+const CONTEXT_BEFORE: usize = 60;
+
+// bytes of later input shown after the offending byte
+const CONTEXT_AFTER: usize = 20;
+
+///
+/// Reader that remembers recent input, so decode errors can be shown in context
+///
+/// Only the most recent read and a few bytes before it are retained, which is
+/// enough to show the line containing any error reported by the decoders.
+///
+pub struct ContextReader<R: Read> {
+    inner: R,
+    // recent input
+    history: Vec<u8>,
+    // offset of the first byte of history in the input
+    history_offset: usize,
+}
+
+impl<R: Read> ContextReader<R> {
+    ///
+    /// Wrap a reader
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - Reader providing the input to be decoded
+    ///
+    pub fn new(inner: R) -> Self {
+        ContextReader {
+            inner,
+            history: Vec::new(),
+            history_offset: 0,
+        }
+    }
+
+    ///
+    /// Replace a decode error with a diagnostic showing the offending input
+    ///
+    /// The diagnostic explains the error, then shows the offending line with a
+    /// caret under the offending byte.  Errors other than decode errors are
+    /// returned unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - Error returned by a decoder reading from this reader
+    /// * `encoding` - Name of the encoding (e.g. "base64")
+    ///
+    pub fn diagnose(&self, error: std::io::Error, encoding: &str) -> std::io::Error {
+        match error
+            .get_ref()
+            .and_then(|inner| inner.downcast_ref::<DecodeError>())
+        {
+            Some(decode_error) => {
+                std::io::Error::new(ErrorKind::InvalidData, self.render(decode_error, encoding))
+            }
+            None => error,
+        }
+    }
+
+    // explain the error and show it in context
+    fn render(&self, error: &DecodeError, encoding: &str) -> String {
+        let position = error.position();
+        let explanation = match *error {
+            DecodeError::InvalidByte { byte, .. } if byte.is_ascii_graphic() => {
+                format!("'{}' is not in the {} alphabet", byte as char, encoding)
+            }
+            DecodeError::InvalidByte { byte, .. } => {
+                format!("byte 0x{:02X} is not in the {} alphabet", byte, encoding)
+            }
+            DecodeError::InvalidPadding { .. } => "padding is misplaced".to_string(),
+            DecodeError::DataAfterPadding { .. } => {
+                "data follows the final padded word".to_string()
+            }
+            DecodeError::TruncatedInput { .. } => {
+                format!("input ends part way through a {} word", encoding)
+            }
+            DecodeError::NonCanonicalTrailingBits { .. } => {
+                "the final character has non-zero trailing bits".to_string()
+            }
+        };
+        let mut message = format!(
+            "{} at line {}, column {}",
+            explanation, position.line, position.column
+        );
+
+        // show the line, if it is still available
+        let offset = position.offset;
+        if offset >= self.history_offset {
+            let index = (offset - self.history_offset).min(self.history.len());
+            let line_start = match self.history[0..index].iter().rposition(|&c| c == b'\n') {
+                Some(i) => Some(i + 1),
+                None if self.history_offset == 0 => Some(0),
+                None => None,
+            };
+            let line_end = self.history[index..]
+                .iter()
+                .position(|&c| c == b'\n' || c == b'\r')
+                .map_or(self.history.len(), |i| index + i);
+
+            // limit the context shown on either side of the offending byte
+            let start = line_start
+                .unwrap_or(0)
+                .max(index.saturating_sub(CONTEXT_BEFORE));
+            let end = line_end.min(index + CONTEXT_AFTER);
+            let prefix = if line_start == Some(start) { "" } else { "..." };
+            let suffix = if end == line_end { "" } else { "..." };
+
+            let excerpt: String = self.history[start..end]
+                .iter()
+                .map(|&c| {
+                    if (0x20..0x7F).contains(&c) {
+                        c as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            message.push_str(&format!(
+                "\n    {}{}{}\n    {}^",
+                prefix,
+                excerpt,
+                suffix,
+                " ".repeat(prefix.len() + index - start)
+            ));
+        }
+
+        message
+    }
+}
+
+impl<R: Read> Read for ContextReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+
+        // keep the tail of earlier input, followed by this read
+        let keep = self.history.len().min(CONTEXT_BEFORE);
+        let discard = self.history.len() - keep;
+        self.history.drain(0..discard);
+        self.history_offset += discard;
+        self.history.extend_from_slice(&buf[0..n]);
+
+        Ok(n)
+    }
+}
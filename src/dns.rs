@@ -0,0 +1,112 @@
+use std::io::{Read, Write};
+
+use crate::alphabet::B32Alphabet;
+use crate::base32::{b32_decode, b32_encode};
+
+// maximum length of a single DNS label (RFC 1035, section 2.3.4)
+const MAX_LABEL_LENGTH: usize = 63;
+
+///
+/// Encode data as a DNS-safe base-32 hostname
+///
+/// Data is encoded as lowercase, unpadded base-32 and split into labels of at
+/// most 63 characters separated by `.`.
+///
+/// # Arguments
+///
+/// * `reader` - Data to encode
+/// * `writer` - Writer to which the encoded hostname will be written
+/// * `extended_hex` - Whether to use the base32hex alphabet
+///
+pub fn b32_dns_encode(
+    reader: &mut impl Read,
+    writer: &mut impl Write,
+    extended_hex: bool,
+) -> Result<(), std::io::Error> {
+    // encode as regular base-32
+    let mut encoded = Vec::new();
+    b32_encode(reader, &mut encoded, None)?;
+
+    // strip padding and translate to the DNS-safe alphabet, which is lowercase
+    let alphabet = dns_alphabet(extended_hex);
+    let symbols = encoded
+        .iter()
+        .take_while(|&&c| c != b'=')
+        .map(|&c| Ok(alphabet.symbols[symbol_value(c)?].to_ascii_lowercase()))
+        .collect::<Result<Vec<u8>, std::io::Error>>()?;
+
+    // output labels
+    for (i, label) in symbols.chunks(MAX_LABEL_LENGTH).enumerate() {
+        if i != 0 {
+            writer.write_all(b".")?;
+        }
+        writer.write_all(label)?;
+    }
+
+    Ok(())
+}
+
+///
+/// Decode a DNS-safe base-32 hostname
+///
+/// Label separators, a trailing root label and whitespace are ignored, and
+/// symbols are matched case-insensitively, as resolvers may alter case.
+///
+/// # Arguments
+///
+/// * `reader` - Encoded hostname reader
+/// * `writer` - Writer to which decoded data will be written
+/// * `extended_hex` - Whether the hostname uses the base32hex alphabet
+///
+pub fn b32_dns_decode(
+    reader: &mut impl Read,
+    writer: &mut impl Write,
+    extended_hex: bool,
+) -> Result<(), std::io::Error> {
+    // the padding to restore depends on the total number of symbols, so read the whole
+    // hostname before decoding any of it
+    let mut input = Vec::new();
+    reader.read_to_end(&mut input)?;
+
+    // translate symbols to the regular base-32 alphabet, leaving out separators
+    let alphabet = dns_alphabet(extended_hex);
+    let mut symbols: Vec<u8> = input
+        .iter()
+        .filter(|&&c| c != b'.' && !c.is_ascii_whitespace())
+        .map(
+            |&c| match alphabet.reverse[c.to_ascii_uppercase() as usize] {
+                value if value < 32 => B32Alphabet::STANDARD.symbols[value as usize],
+                // anything else is invalid, and stays invalid for the decoder as no
+                // symbol in the regular alphabet is lower-case
+                _ => c.to_ascii_lowercase(),
+            },
+        )
+        .collect();
+
+    // restore padding
+    while !symbols.len().is_multiple_of(8) {
+        symbols.push(b'=');
+    }
+
+    b32_decode(&mut symbols.as_slice(), writer, false)
+}
+
+// alphabet whose symbols, lowercased, are used in hostnames
+fn dns_alphabet(extended_hex: bool) -> &'static B32Alphabet {
+    if extended_hex {
+        &B32Alphabet::EXTENDED_HEX
+    } else {
+        &B32Alphabet::STANDARD
+    }
+}
+
+// value of a symbol in the standard base-32 alphabet
+fn symbol_value(c: u8) -> Result<usize, std::io::Error> {
+    match B32Alphabet::STANDARD.reverse[c as usize] {
+        value if value < 32 => Ok(value as usize),
+        _ => Err(std::io::Error::other(format!(
+            "unexpected base-32 symbol {:?}",
+            c as char
+        ))),
+    }
+}
@@ -0,0 +1,266 @@
+use std::fmt;
+use std::io::ErrorKind;
+
+///
+/// Location of a byte in decoder input
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    /// Byte offset from the start of the input (0-based)
+    pub offset: usize,
+    /// Line number (1-based)
+    pub line: usize,
+    /// Column number, in bytes (1-based)
+    pub column: usize,
+}
+
+///
+/// Reason decoding failed, and where
+///
+/// Decoders return this wrapped in a `std::io::Error` of kind `InvalidData`,
+/// from which it can be recovered with `get_ref` and `downcast_ref`.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// A byte that is not in the alphabet, padding or whitespace
+    InvalidByte { byte: u8, position: Position },
+    /// Padding where it is not allowed
+    InvalidPadding { position: Position },
+    /// Data following the final padded word
+    DataAfterPadding { position: Position },
+    /// Input ended part way through a word
+    TruncatedInput { position: Position },
+    /// Non-zero bits in the final symbol that decoding would discard
+    NonCanonicalTrailingBits { position: Position },
+}
+
+impl DecodeError {
+    ///
+    /// Location in the input at which the error was detected
+    ///
+    pub fn position(&self) -> Position {
+        match *self {
+            DecodeError::InvalidByte { position, .. }
+            | DecodeError::InvalidPadding { position }
+            | DecodeError::DataAfterPadding { position }
+            | DecodeError::TruncatedInput { position }
+            | DecodeError::NonCanonicalTrailingBits { position } => position,
+        }
+    }
+
+    // the same error, located within a larger input in which the decoded
+    // data starts at `start`
+    pub(crate) fn relocate(self, start: Position) -> Self {
+        let Position {
+            offset,
+            line,
+            column,
+        } = self.position();
+        let position = Position {
+            offset: start.offset + offset,
+            line: start.line + line - 1,
+            column: if line == 1 {
+                start.column + column - 1
+            } else {
+                column
+            },
+        };
+        match self {
+            DecodeError::InvalidByte { byte, .. } => DecodeError::InvalidByte { byte, position },
+            DecodeError::InvalidPadding { .. } => DecodeError::InvalidPadding { position },
+            DecodeError::DataAfterPadding { .. } => DecodeError::DataAfterPadding { position },
+            DecodeError::TruncatedInput { .. } => DecodeError::TruncatedInput { position },
+            DecodeError::NonCanonicalTrailingBits { .. } => {
+                DecodeError::NonCanonicalTrailingBits { position }
+            }
+        }
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            DecodeError::InvalidByte { byte, .. } if byte.is_ascii_graphic() => {
+                write!(f, "invalid character '{}'", byte as char)?
+            }
+            DecodeError::InvalidByte { byte, .. } => write!(f, "invalid byte 0x{:02X}", byte)?,
+            DecodeError::InvalidPadding { .. } => write!(f, "invalid padding")?,
+            DecodeError::DataAfterPadding { .. } => write!(f, "data after padding")?,
+            DecodeError::TruncatedInput { .. } => write!(f, "truncated input")?,
+            DecodeError::NonCanonicalTrailingBits { .. } => write!(f, "non-zero trailing bits")?,
+        }
+        let position = self.position();
+        write!(f, " at line {}, column {}", position.line, position.column)
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl From<DecodeError> for std::io::Error {
+    fn from(error: DecodeError) -> Self {
+        std::io::Error::new(ErrorKind::InvalidData, error)
+    }
+}
+
+///
+/// Reason an alphabet was rejected
+///
+/// This converts to a `std::io::Error` of kind `InvalidInput`.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlphabetError {
+    /// A symbol that is not printable ASCII
+    InvalidSymbol(u8),
+    /// A symbol that appears more than once
+    DuplicateSymbol(u8),
+    /// The `=` padding character used as a symbol
+    PaddingSymbol,
+}
+
+impl fmt::Display for AlphabetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            AlphabetError::InvalidSymbol(byte) => {
+                write!(f, "invalid alphabet symbol 0x{:02X}", byte)
+            }
+            AlphabetError::DuplicateSymbol(byte) => {
+                write!(f, "duplicate alphabet symbol '{}'", byte as char)
+            }
+            AlphabetError::PaddingSymbol => write!(f, "alphabet contains the padding character"),
+        }
+    }
+}
+
+impl std::error::Error for AlphabetError {}
+
+impl From<AlphabetError> for std::io::Error {
+    fn from(error: AlphabetError) -> Self {
+        std::io::Error::new(ErrorKind::InvalidInput, error)
+    }
+}
+
+// relocate a `DecodeError` within a larger input in which the decoded data
+// starts at `start`, leaving any other error as it is
+pub(crate) fn relocate_error(error: std::io::Error, start: Option<Position>) -> std::io::Error {
+    match (
+        error
+            .get_ref()
+            .and_then(|inner| inner.downcast_ref::<DecodeError>()),
+        start,
+    ) {
+        (Some(decode_error), Some(start)) => decode_error.relocate(start).into(),
+        _ => error,
+    }
+}
+
+// tracks the position of each buffer of input passed through a decoder
+pub(crate) struct PositionTracker {
+    // offset of the start of the current buffer
+    offset: usize,
+    // line number at the start of the current buffer
+    line: usize,
+    // offset of the start of that line
+    line_start: usize,
+}
+
+impl PositionTracker {
+    pub(crate) fn new() -> Self {
+        PositionTracker {
+            offset: 0,
+            line: 1,
+            line_start: 0,
+        }
+    }
+
+    // position of the byte at `index` in the current buffer
+    pub(crate) fn locate(&self, buffer: &[u8], index: usize) -> Position {
+        let preceding = &buffer[0..index];
+        let line = self.line + count_newlines(preceding);
+        let line_start = match preceding.iter().rposition(|&b| b == b'\n') {
+            Some(newline) => self.offset + newline + 1,
+            None => self.line_start,
+        };
+        let offset = self.offset + index;
+        Position {
+            offset,
+            line,
+            column: offset - line_start + 1,
+        }
+    }
+
+    // error for an invalid byte at `index` in the current buffer
+    #[cold]
+    pub(crate) fn invalid_byte(&self, buffer: &[u8], index: usize) -> std::io::Error {
+        DecodeError::InvalidByte {
+            byte: buffer[index],
+            position: self.locate(buffer, index),
+        }
+        .into()
+    }
+
+    // error for invalid padding at `index` in the current buffer
+    #[cold]
+    pub(crate) fn invalid_padding(&self, buffer: &[u8], index: usize) -> std::io::Error {
+        DecodeError::InvalidPadding {
+            position: self.locate(buffer, index),
+        }
+        .into()
+    }
+
+    // error for data after padding at `index` in the current buffer
+    #[cold]
+    pub(crate) fn data_after_padding(&self, buffer: &[u8], index: usize) -> std::io::Error {
+        DecodeError::DataAfterPadding {
+            position: self.locate(buffer, index),
+        }
+        .into()
+    }
+
+    // error for non-zero trailing bits in the char at `index` in the current buffer
+    #[cold]
+    pub(crate) fn non_canonical_trailing_bits(
+        &self,
+        buffer: &[u8],
+        index: usize,
+    ) -> std::io::Error {
+        DecodeError::NonCanonicalTrailingBits {
+            position: self.locate(buffer, index),
+        }
+        .into()
+    }
+
+    // position immediately after all input seen so far
+    pub(crate) fn end(&self) -> Position {
+        Position {
+            offset: self.offset,
+            line: self.line,
+            column: self.offset - self.line_start + 1,
+        }
+    }
+
+    // move past the current buffer
+    pub(crate) fn advance(&mut self, buffer: &[u8]) {
+        self.line += count_newlines(buffer);
+        if let Some(newline) = buffer.iter().rposition(|&b| b == b'\n') {
+            self.line_start = self.offset + newline + 1;
+        }
+        self.offset += buffer.len();
+    }
+}
+
+// count newlines a word at a time, as this is done for every buffer of input
+fn count_newlines(buffer: &[u8]) -> usize {
+    const LOW_BITS: u64 = 0x7F7F_7F7F_7F7F_7F7F;
+    const NEWLINES: u64 = 0x0A0A_0A0A_0A0A_0A0A;
+
+    let chunks = buffer.chunks_exact(8);
+    let remainder = chunks.remainder();
+    let mut count = 0;
+    for chunk in chunks {
+        // zero out newline bytes, then set the high bit of exactly the zero bytes
+        let x = u64::from_ne_bytes(chunk.try_into().unwrap()) ^ NEWLINES;
+        let zeros = !(((x & LOW_BITS) + LOW_BITS) | x | LOW_BITS);
+        count += zeros.count_ones() as usize;
+    }
+    count + remainder.iter().filter(|&&b| b == b'\n').count()
+}
@@ -0,0 +1,226 @@
+use std::io::{Read, Write};
+
+use crate::common::{hex_value, parse_hex_bytes, push_hex_byte, LOWER_HEX_DIGITS};
+
+///
+/// Format data as an xxd-style hexdump
+///
+/// Each line holds an 8-digit hex offset, the data in hex (split into groups of
+/// `group_size` bytes) and an ASCII gutter in which non-printable bytes are
+/// shown as `.`.
+///
+/// # Arguments
+///
+/// * `reader` - Data to dump
+/// * `writer` - Writer to which the hexdump will be written
+/// * `columns` - Number of bytes per line
+/// * `group_size` - Number of bytes per group (0 to disable grouping)
+///
+pub fn hexdump_encode(
+    reader: &mut impl Read,
+    writer: &mut impl Write,
+    columns: usize,
+    group_size: usize,
+) -> Result<(), std::io::Error> {
+    // sanity-check parameters
+    if columns == 0 {
+        return Err(std::io::Error::other("invalid column count"));
+    }
+
+    // no grouping is equivalent to a single group per line
+    let group_size = if group_size == 0 { columns } else { group_size };
+
+    // width of the hex field, including the space after each group
+    let hex_width = columns * 2 + columns.div_ceil(group_size);
+
+    let mut data = vec![0; columns];
+    let mut line = Vec::new();
+    let mut offset: usize = 0;
+    loop {
+        // fill a line's worth of data
+        let n = read_full(reader, &mut data)?;
+        if n == 0 {
+            break;
+        }
+
+        // output offset
+        line.clear();
+        line.extend_from_slice(format!("{:08x}: ", offset).as_bytes());
+
+        // output hex, padded to full width
+        let hex_start = line.len();
+        for (i, &b) in data[0..n].iter().enumerate() {
+            // lower-case, as used by xxd
+            push_hex_byte(&mut line, b, LOWER_HEX_DIGITS);
+            if (i + 1) % group_size == 0 {
+                line.push(b' ');
+            }
+        }
+        line.resize(hex_start + hex_width + 1, b' ');
+
+        // output ASCII gutter
+        for &b in &data[0..n] {
+            line.push(if (0x20..0x7F).contains(&b) { b } else { b'.' });
+        }
+        line.push(b'\n');
+
+        writer.write_all(&line)?;
+        offset += n;
+    }
+
+    Ok(())
+}
+
+///
+/// Format data as a plain hexdump, as produced by `xxd -p`
+///
+/// # Arguments
+///
+/// * `reader` - Data to dump
+/// * `writer` - Writer to which the hexdump will be written
+/// * `columns` - Number of bytes per line
+///
+pub fn hexdump_plain_encode(
+    reader: &mut impl Read,
+    writer: &mut impl Write,
+    columns: usize,
+) -> Result<(), std::io::Error> {
+    // sanity-check parameters
+    if columns == 0 {
+        return Err(std::io::Error::other("invalid column count"));
+    }
+
+    let mut data = vec![0; columns];
+    let mut line = Vec::new();
+    loop {
+        // fill a line's worth of data
+        let n = read_full(reader, &mut data)?;
+        if n == 0 {
+            break;
+        }
+
+        // output hex
+        line.clear();
+        for &b in &data[0..n] {
+            push_hex_byte(&mut line, b, LOWER_HEX_DIGITS);
+        }
+        line.push(b'\n');
+
+        writer.write_all(&line)?;
+    }
+
+    Ok(())
+}
+
+///
+/// Parse an xxd-style hexdump back into binary
+///
+/// The hex field of each line ends at the first pair of spaces, so the ASCII
+/// gutter is ignored.  Gaps between line offsets are filled with zeros, which
+/// are written as they are found, so data may be written before an error.
+///
+/// # Arguments
+///
+/// * `reader` - Hexdump reader
+/// * `writer` - Writer to which the binary data will be written
+///
+pub fn hexdump_decode(
+    reader: &mut impl Read,
+    writer: &mut impl Write,
+) -> Result<(), std::io::Error> {
+    // read the whole dump
+    let mut input = Vec::new();
+    reader.read_to_end(&mut input)?;
+
+    // offsets are untrusted, so gaps are streamed rather than laid out in memory
+    let zeros = [0; 4096];
+    let mut written = 0;
+    for (line_index, line) in input.split(|&c| c == b'\n').enumerate() {
+        let invalid =
+            || std::io::Error::other(format!("invalid hexdump on line {}", line_index + 1));
+
+        if line.trim_ascii().is_empty() {
+            continue;
+        }
+
+        // parse the offset
+        let colon = line.iter().position(|&c| c == b':').ok_or_else(invalid)?;
+        let offset = line[0..colon]
+            .trim_ascii()
+            .iter()
+            .try_fold(0usize, |offset, &c| {
+                offset.checked_mul(16)?.checked_add(hex_value(c)? as usize)
+            })
+            .ok_or_else(invalid)?;
+
+        // lines may skip forward, but xxd never goes backwards
+        if offset < written {
+            return Err(invalid());
+        }
+        while written < offset {
+            let n = (offset - written).min(zeros.len());
+            writer.write_all(&zeros[0..n])?;
+            written += n;
+        }
+
+        // parse the hex field, which ends at a double space
+        let mut field = &line[colon + 1..];
+        if field.first() == Some(&b' ') {
+            field = &field[1..];
+        }
+        let end = field
+            .windows(2)
+            .position(|w| w == b"  ")
+            .unwrap_or(field.len());
+        let digits: Vec<u8> = field[0..end]
+            .iter()
+            .filter(|&&c| c != b' ' && c != b'\r')
+            .copied()
+            .collect();
+        let data = parse_hex_bytes(&digits).ok_or_else(invalid)?;
+        writer.write_all(&data)?;
+        written += data.len();
+    }
+
+    Ok(())
+}
+
+///
+/// Parse a plain hexdump, as produced by `xxd -p`, back into binary
+///
+/// # Arguments
+///
+/// * `reader` - Hexdump reader
+/// * `writer` - Writer to which the binary data will be written
+///
+pub fn hexdump_plain_decode(
+    reader: &mut impl Read,
+    writer: &mut impl Write,
+) -> Result<(), std::io::Error> {
+    // read the whole dump
+    let mut input = Vec::new();
+    reader.read_to_end(&mut input)?;
+
+    // whitespace is insignificant
+    let digits: Vec<u8> = input
+        .into_iter()
+        .filter(|c| !c.is_ascii_whitespace())
+        .collect();
+    let output =
+        parse_hex_bytes(&digits).ok_or_else(|| std::io::Error::other("invalid hexdump"))?;
+
+    writer.write_all(&output)
+}
+
+// read until the buffer is full or the reader is exhausted
+fn read_full(reader: &mut impl Read, buffer: &mut [u8]) -> Result<usize, std::io::Error> {
+    let mut n = 0;
+    while n < buffer.len() {
+        let bytes_read = reader.read(&mut buffer[n..])?;
+        if bytes_read == 0 {
+            break;
+        }
+        n += bytes_read;
+    }
+    Ok(n)
+}
@@ -0,0 +1,186 @@
+use std::io::{BufRead, BufReader, Read, Write};
+
+use crate::common::{parse_hex_bytes, push_hex_byte, write_image, HEX_DIGITS};
+
+// record types
+const DATA: u8 = 0x00;
+const END_OF_FILE: u8 = 0x01;
+const EXTENDED_SEGMENT_ADDRESS: u8 = 0x02;
+const START_SEGMENT_ADDRESS: u8 = 0x03;
+const EXTENDED_LINEAR_ADDRESS: u8 = 0x04;
+const START_LINEAR_ADDRESS: u8 = 0x05;
+
+///
+/// Encode a binary image as Intel HEX records
+///
+/// Extended linear address records are emitted whenever the upper 16 bits of
+/// the address change, and data records never cross a 64KiB boundary.
+///
+/// # Arguments
+///
+/// * `reader` - Binary image to encode
+/// * `writer` - Writer to which records will be written
+/// * `base_address` - Address of the first byte of the image
+/// * `record_length` - Maximum number of data bytes per record (1-255)
+///
+pub fn ihex_encode(
+    reader: &mut impl Read,
+    writer: &mut impl Write,
+    base_address: u32,
+    record_length: usize,
+) -> Result<(), std::io::Error> {
+    // sanity-check parameters
+    if record_length == 0 || record_length > 255 {
+        return Err(std::io::Error::other("invalid record length"));
+    }
+
+    let mut line = Vec::new();
+    let mut data = Vec::with_capacity(record_length);
+    let mut upper_address: Option<u16> = None;
+    let mut address = base_address as u64;
+    loop {
+        // read as much data as fits in the record and the segment
+        let segment_remaining = 0x10000 - (address & 0xFFFF);
+        data.clear();
+        reader
+            .by_ref()
+            .take((record_length as u64).min(segment_remaining))
+            .read_to_end(&mut data)?;
+        if data.is_empty() {
+            break;
+        }
+        if address + data.len() as u64 > 1 << 32 {
+            return Err(std::io::Error::other("image exceeds 32-bit address space"));
+        }
+
+        // switch to a new 64KiB segment if required
+        let upper = (address >> 16) as u16;
+        if upper_address != Some(upper) && (upper != 0 || upper_address.is_some()) {
+            write_record(
+                &mut line,
+                0,
+                EXTENDED_LINEAR_ADDRESS,
+                &upper.to_be_bytes(),
+                writer,
+            )?;
+        }
+        upper_address = Some(upper);
+
+        // output the data
+        write_record(&mut line, address as u16, DATA, &data, writer)?;
+        address += data.len() as u64;
+    }
+
+    write_record(&mut line, 0, END_OF_FILE, &[], writer)
+}
+
+///
+/// Decode Intel HEX records into a binary image
+///
+/// The image starts at the lowest address present in the records, and gaps
+/// between records are filled with 0xFF.
+///
+/// # Arguments
+///
+/// * `reader` - Intel HEX records reader
+/// * `writer` - Writer to which the binary image will be written
+///
+pub fn ihex_decode(reader: &mut impl Read, writer: &mut impl Write) -> Result<(), std::io::Error> {
+    // Intel HEX records may arrive out of address order, so collect the data of each before
+    // assembling the image
+    let mut segments: Vec<(u64, Vec<u8>)> = Vec::new();
+    let mut upper_address: u64 = 0;
+    let mut reached_end = false;
+
+    for (line_index, line) in BufReader::new(reader).split(b'\n').enumerate() {
+        let line = line?;
+        let line = line.trim_ascii();
+        if line.is_empty() {
+            continue;
+        }
+
+        // nothing may follow the end of file record
+        let invalid = |reason: &str| {
+            std::io::Error::other(format!(
+                "invalid record on line {}: {}",
+                line_index + 1,
+                reason
+            ))
+        };
+        if reached_end {
+            return Err(invalid("data after end of file record"));
+        }
+
+        // parse the record
+        if line[0] != b':' {
+            return Err(invalid("missing start code"));
+        }
+        let record = parse_hex_bytes(&line[1..]).ok_or_else(|| invalid("invalid hex digits"))?;
+        if record.len() < 5 || record.len() != record[0] as usize + 5 {
+            return Err(invalid("invalid length"));
+        }
+        if record.iter().fold(0u8, |sum, &b| sum.wrapping_add(b)) != 0 {
+            return Err(invalid("checksum mismatch"));
+        }
+        let address = u16::from_be_bytes([record[1], record[2]]) as u64;
+        let record_type = record[3];
+        let data = &record[4..record.len() - 1];
+
+        match record_type {
+            DATA => segments.push((upper_address + address, data.to_vec())),
+            END_OF_FILE => reached_end = true,
+            EXTENDED_SEGMENT_ADDRESS | EXTENDED_LINEAR_ADDRESS => {
+                if data.len() != 2 {
+                    return Err(invalid("invalid address record"));
+                }
+                let value = u16::from_be_bytes([data[0], data[1]]) as u64;
+                upper_address = if record_type == EXTENDED_SEGMENT_ADDRESS {
+                    value << 4
+                } else {
+                    value << 16
+                };
+            }
+            START_SEGMENT_ADDRESS | START_LINEAR_ADDRESS => {
+                // start addresses have no meaning in a binary image
+                if data.len() != 4 {
+                    return Err(invalid("invalid start address record"));
+                }
+            }
+            _ => return Err(invalid("unknown record type")),
+        }
+    }
+
+    if !reached_end {
+        return Err(std::io::Error::other("missing end of file record"));
+    }
+
+    write_image(segments, writer)
+}
+
+// output a single record
+fn write_record(
+    line: &mut Vec<u8>,
+    address: u16,
+    record_type: u8,
+    data: &[u8],
+    writer: &mut impl Write,
+) -> Result<(), std::io::Error> {
+    let [address_hi, address_lo] = address.to_be_bytes();
+    let header = [data.len() as u8, address_hi, address_lo, record_type];
+
+    // checksum is the two's complement of the sum of all record bytes
+    let sum = header
+        .iter()
+        .chain(data)
+        .fold(0u8, |sum, &b| sum.wrapping_add(b));
+
+    line.clear();
+    line.push(b':');
+    for &b in header.iter().chain(data) {
+        push_hex_byte(line, b, HEX_DIGITS);
+    }
+    push_hex_byte(line, sum.wrapping_neg(), HEX_DIGITS);
+    line.push(b'\n');
+
+    writer.write_all(line)
+}
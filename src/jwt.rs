@@ -0,0 +1,397 @@
+use std::io::{Read, Write};
+
+use crate::alphabet::Alphabet;
+use crate::common::{hex_string, HEX_DIGITS};
+use crate::config::{B64Config, DecodePolicy, Padding};
+use crate::error::{DecodeError, Position};
+
+// segments are unpadded base64url, with nothing else allowed (RFC 7515, section 2)
+const SEGMENT: B64Config = B64Config::new(Alphabet::<64>::URL_SAFE)
+    .padding(Padding::Forbidden)
+    .decode_policy(DecodePolicy::NoWhitespace);
+
+// names of the segments of a JWS and a JWE, in order
+const JWS_SEGMENTS: [&str; 3] = ["Header", "Payload", "Signature"];
+const JWE_SEGMENTS: [&str; 5] = [
+    "Header",
+    "Encrypted key",
+    "Initialization vector",
+    "Ciphertext",
+    "Authentication tag",
+];
+
+// nesting beyond this is almost certainly not a real token
+const MAX_DEPTH: usize = 64;
+
+// payload claims holding seconds since the epoch (RFC 7519, section 4.1)
+const TIME_CLAIMS: [&str; 3] = ["exp", "iat", "nbf"];
+
+///
+/// A decoded segment of a compact JWS or JWE
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JwtSegment {
+    /// Offset of the segment in the token
+    pub offset: usize,
+    /// Decoded segment, or why it could not be decoded, located within the token
+    pub data: Result<Vec<u8>, DecodeError>,
+}
+
+///
+/// Split a compact JWS (3 segments) or JWE (5 segments) on `.`, and decode
+/// each segment
+///
+/// Segments that are not valid unpadded base64url are returned as errors,
+/// rather than failing the whole token.
+///
+/// # Arguments
+///
+/// * `token` - Compact JWS or JWE
+///
+pub fn jwt_split(token: &[u8]) -> Result<Vec<JwtSegment>, std::io::Error> {
+    let mut segments = Vec::new();
+    let mut offset = 0;
+    for encoded in token.split(|&c| c == b'.') {
+        let data = SEGMENT.decode_to_vec(encoded).map_err(|error| {
+            let start = Position {
+                offset,
+                line: 1,
+                column: offset + 1,
+            };
+            error
+                .get_ref()
+                .and_then(|inner| inner.downcast_ref::<DecodeError>())
+                .expect("decoding a slice only fails on invalid input")
+                .relocate(start)
+        });
+        segments.push(JwtSegment { offset, data });
+        offset += encoded.len() + 1;
+    }
+
+    if segments.len() != JWS_SEGMENTS.len() && segments.len() != JWE_SEGMENTS.len() {
+        return Err(std::io::Error::other(format!(
+            "invalid JWT: expected 3 or 5 segments, found {}",
+            segments.len()
+        )));
+    }
+    Ok(segments)
+}
+
+///
+/// Print a compact JWS or JWE for inspection
+///
+/// JSON segments are pretty-printed, with `exp`, `iat` and `nbf` claims
+/// annotated as UTC timestamps, and other segments are printed in hex.
+/// Malformed segments are flagged, and the first is returned as an error,
+/// located within the input, once everything has been printed.
+///
+/// # Arguments
+///
+/// * `reader` - Token reader
+/// * `writer` - Writer to which the inspection will be written
+///
+pub fn jwt_inspect(reader: &mut impl Read, writer: &mut impl Write) -> Result<(), std::io::Error> {
+    // segments are labelled by whether the token is a JWS or a JWE, which is only known
+    // from the number of segments, so read the whole token before printing any of it
+    let mut input = Vec::new();
+    reader.read_to_end(&mut input)?;
+
+    // leading whitespace must be accounted for when locating errors
+    let leading = &input[0..input.len() - input.trim_ascii_start().len()];
+    let token_start = Position {
+        offset: leading.len(),
+        line: leading.iter().filter(|&&c| c == b'\n').count() + 1,
+        column: leading.len()
+            - leading
+                .iter()
+                .rposition(|&c| c == b'\n')
+                .map_or(0, |i| i + 1)
+            + 1,
+    };
+    let segments = jwt_split(input.trim_ascii())?;
+    let names: &[&str] = if segments.len() == JWS_SEGMENTS.len() {
+        &JWS_SEGMENTS
+    } else {
+        &JWE_SEGMENTS
+    };
+
+    let mut first_error = None;
+    for (index, (segment, name)) in segments.iter().zip(names).enumerate() {
+        if index > 0 {
+            writeln!(writer)?;
+        }
+
+        let data = match &segment.data {
+            Ok(data) => data,
+            Err(error) => {
+                let error = error.relocate(token_start);
+                writeln!(
+                    writer,
+                    "{}: malformed, {} (offset {})",
+                    name,
+                    error,
+                    error.position().offset
+                )?;
+                first_error.get_or_insert_with(|| error.into());
+                continue;
+            }
+        };
+
+        // the header, and a JWS payload, are JSON
+        let is_json = index == 0 || (index == 1 && names.len() == JWS_SEGMENTS.len());
+        if !is_json {
+            writeln!(writer, "{}: {} bytes", name, data.len())?;
+            if !data.is_empty() {
+                writeln!(writer, "{}", hex_string(data, HEX_DIGITS, ""))?;
+            }
+            continue;
+        }
+        match pretty_json(data, index == 1) {
+            Ok(json) => writeln!(writer, "{}:\n{}", name, json)?,
+            Err(json_offset) => {
+                writeln!(
+                    writer,
+                    "{}: malformed, invalid JSON at offset {} of decoded segment",
+                    name, json_offset
+                )?;
+                first_error.get_or_insert_with(|| {
+                    std::io::Error::other(format!(
+                        "invalid JWT: {} is not JSON (offset {} of decoded segment)",
+                        name.to_lowercase(),
+                        json_offset
+                    ))
+                });
+            }
+        }
+    }
+
+    match first_error {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+// pretty-print JSON, annotating top-level time claims if asked, or return the
+// offset of the first error
+fn pretty_json(json: &[u8], annotate: bool) -> Result<String, usize> {
+    let mut printer = JsonPrinter {
+        input: json,
+        position: 0,
+        output: String::new(),
+        annotate,
+    };
+    printer.value(0)?;
+    printer.skip_whitespace();
+    if printer.position != json.len() {
+        return Err(printer.position);
+    }
+    Ok(printer.output)
+}
+
+// recursive-descent JSON pretty-printer
+struct JsonPrinter<'a> {
+    input: &'a [u8],
+    position: usize,
+    output: String,
+    annotate: bool,
+}
+
+impl<'a> JsonPrinter<'a> {
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.position += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.position).copied()
+    }
+
+    // consume `expected`, after any whitespace
+    fn expect(&mut self, expected: u8) -> Result<(), usize> {
+        self.skip_whitespace();
+        if self.peek() != Some(expected) {
+            return Err(self.position);
+        }
+        self.position += 1;
+        Ok(())
+    }
+
+    fn indent(&mut self, depth: usize) {
+        self.output.push('\n');
+        self.output.push_str(&"  ".repeat(depth));
+    }
+
+    fn value(&mut self, depth: usize) -> Result<(), usize> {
+        self.skip_whitespace();
+        if depth > MAX_DEPTH {
+            return Err(self.position);
+        }
+        match self.peek() {
+            Some(b'{') => self.container(depth, b'}'),
+            Some(b'[') => self.container(depth, b']'),
+            Some(b'"') => {
+                let string = self.string()?;
+                self.output.push_str(string);
+                Ok(())
+            }
+            Some(b'-' | b'0'..=b'9') => {
+                let number = self.number()?;
+                self.output.push_str(number);
+                Ok(())
+            }
+            _ => {
+                for literal in ["true", "false", "null"] {
+                    if self.input[self.position..].starts_with(literal.as_bytes()) {
+                        self.position += literal.len();
+                        self.output.push_str(literal);
+                        return Ok(());
+                    }
+                }
+                Err(self.position)
+            }
+        }
+    }
+
+    // an object or array, one member per line
+    fn container(&mut self, depth: usize, close: u8) -> Result<(), usize> {
+        let is_object = close == b'}';
+        self.output.push(if is_object { '{' } else { '[' });
+        self.position += 1;
+        self.skip_whitespace();
+        if self.peek() == Some(close) {
+            self.position += 1;
+            self.output.push(close as char);
+            return Ok(());
+        }
+
+        loop {
+            self.indent(depth + 1);
+
+            let mut key = None;
+            if is_object {
+                self.skip_whitespace();
+                if self.peek() != Some(b'"') {
+                    return Err(self.position);
+                }
+                let string = self.string()?;
+                self.output.push_str(string);
+                self.output.push_str(": ");
+                self.expect(b':')?;
+                key = Some(&string[1..string.len() - 1]);
+            }
+
+            let value_start = self.output.len();
+            self.value(depth + 1)?;
+            let annotation = match key {
+                Some(key) if self.annotate && depth == 0 && TIME_CLAIMS.contains(&key) => {
+                    timestamp(&self.output[value_start..])
+                }
+                _ => None,
+            };
+
+            self.skip_whitespace();
+            let more = self.peek() == Some(b',');
+            if more {
+                self.position += 1;
+                self.output.push(',');
+            }
+            if let Some(annotation) = annotation {
+                self.output.push_str("  // ");
+                self.output.push_str(&annotation);
+            }
+            if !more {
+                break;
+            }
+        }
+
+        self.expect(close)?;
+        self.indent(depth);
+        self.output.push(close as char);
+        Ok(())
+    }
+
+    // a string, returned as written, with its quotes
+    fn string(&mut self) -> Result<&'a str, usize> {
+        let input = self.input;
+        let start = self.position;
+        self.position += 1;
+        loop {
+            match self.peek() {
+                Some(b'"') => break,
+                Some(b'\\') => self.position += 2,
+                Some(0x00..=0x1F) | None => return Err(self.position),
+                Some(_) => self.position += 1,
+            }
+        }
+        self.position += 1;
+        std::str::from_utf8(&input[start..self.position]).map_err(|_| start)
+    }
+
+    // a number, returned as written
+    fn number(&mut self) -> Result<&'a str, usize> {
+        let input = self.input;
+        let start = self.position;
+        let digits = |printer: &mut Self| {
+            let first = printer.position;
+            while matches!(printer.peek(), Some(b'0'..=b'9')) {
+                printer.position += 1;
+            }
+            if printer.position == first {
+                Err(printer.position)
+            } else {
+                Ok(())
+            }
+        };
+
+        if self.peek() == Some(b'-') {
+            self.position += 1;
+        }
+        digits(self)?;
+        if self.peek() == Some(b'.') {
+            self.position += 1;
+            digits(self)?;
+        }
+        if matches!(self.peek(), Some(b'e' | b'E')) {
+            self.position += 1;
+            if matches!(self.peek(), Some(b'+' | b'-')) {
+                self.position += 1;
+            }
+            digits(self)?;
+        }
+        Ok(std::str::from_utf8(&input[start..self.position]).unwrap())
+    }
+}
+
+// seconds since the epoch as a UTC timestamp, if it is a whole number
+fn timestamp(number: &str) -> Option<String> {
+    let seconds = number.parse::<i64>().ok()?;
+    let days = seconds.div_euclid(86400);
+    let time = seconds.rem_euclid(86400);
+
+    // civil date from days since 1970-01-01, in 400-year eras starting in March
+    let z = days.checked_add(719468)?;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    Some(format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    ))
+}
@@ -1,6 +1,42 @@
+mod alphabet;
+mod armor;
 mod base32;
 mod base64;
 mod common;
+mod config;
+mod data_uri;
+mod der;
+mod diagnostic;
+mod digest;
+mod dns;
+mod error;
+mod hexdump;
+mod ihex;
+mod jwt;
+mod line_wrapper;
+mod mime_header;
+mod pem;
+mod proquint;
+mod srec;
+mod ssh_key;
 
+pub use alphabet::*;
+pub use armor::*;
 pub use base32::*;
 pub use base64::*;
+pub use config::*;
+pub use data_uri::*;
+pub use der::*;
+pub use diagnostic::*;
+pub use digest::*;
+pub use dns::*;
+pub use error::*;
+pub use hexdump::*;
+pub use ihex::*;
+pub use jwt::*;
+pub use line_wrapper::*;
+pub use mime_header::*;
+pub use pem::*;
+pub use proquint::*;
+pub use srec::*;
+pub use ssh_key::*;
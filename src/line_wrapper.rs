@@ -0,0 +1,207 @@
+use std::io::Write;
+
+///
+/// Writer that wraps data into lines before passing it to another writer
+///
+/// Each line holds at most `width` bytes of data, not counting its prefix, and
+/// the column is tracked across calls to `write`.  Lines are separated only
+/// once more data follows, so the final line is ended by `finish`, unless
+/// nothing was written.  If the writer is dropped without calling `finish`
+/// this happens automatically, but any error is lost.
+///
+pub struct LineWrapper<W: Write> {
+    // taken by `finish`
+    writer: Option<W>,
+    // column at which to wrap
+    width: Option<usize>,
+    // written at the start of each line
+    prefix: Vec<u8>,
+    // written between lines
+    separator: Vec<u8>,
+    // whether or not `finish` ends the final line
+    final_newline: bool,
+    // current output column, not counting the prefix
+    column: usize,
+}
+
+impl<W: Write> LineWrapper<W> {
+    ///
+    /// Wrap a writer
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - Writer to which lines will be written
+    /// * `width` - Column at which to wrap lines, or `None` for a single line
+    ///
+    pub fn new(writer: W, width: Option<usize>) -> Result<Self, std::io::Error> {
+        // sanity-check parameters
+        if width == Some(0) {
+            return Err(std::io::Error::other("cannot wrap on column 0"));
+        }
+
+        Ok(LineWrapper {
+            writer: Some(writer),
+            width,
+            prefix: Vec::new(),
+            separator: b"\n".to_vec(),
+            final_newline: true,
+            column: 0,
+        })
+    }
+
+    ///
+    /// Set the bytes written at the start of each line
+    ///
+    /// # Arguments
+    ///
+    /// * `prefix` - Line prefix (empty by default)
+    ///
+    pub fn prefix(mut self, prefix: &[u8]) -> Self {
+        self.prefix = prefix.to_vec();
+        self
+    }
+
+    ///
+    /// Indent each line with spaces
+    ///
+    /// # Arguments
+    ///
+    /// * `indent` - Number of spaces at the start of each line
+    ///
+    pub fn indent(self, indent: usize) -> Self {
+        self.prefix(&b" ".repeat(indent))
+    }
+
+    ///
+    /// Set the bytes written between lines, and after the final line
+    ///
+    /// # Arguments
+    ///
+    /// * `separator` - Line separator (`\n` by default)
+    ///
+    pub fn separator(mut self, separator: &[u8]) -> Self {
+        self.separator = separator.to_vec();
+        self
+    }
+
+    ///
+    /// Set whether `finish` ends the final line with the separator
+    ///
+    /// # Arguments
+    ///
+    /// * `final_newline` - Whether or not to end the final line (on by default)
+    ///
+    pub fn final_newline(mut self, final_newline: bool) -> Self {
+        self.final_newline = final_newline;
+        self
+    }
+
+    ///
+    /// Current output column, not counting the prefix
+    ///
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    // continue a line already partly written, at `column`
+    pub(crate) fn resume_at(mut self, column: usize) -> Self {
+        self.column = column;
+        self
+    }
+
+    // whether or not there is a line to end, which is only once data has been written
+    fn ends_line(&self) -> bool {
+        self.final_newline && self.column > 0
+    }
+
+    ///
+    /// End the final line, unless disabled or empty, and return the wrapped writer
+    ///
+    pub fn finish(self) -> Result<W, std::io::Error> {
+        let mut writer = self.end()?;
+        writer.flush()?;
+        Ok(writer)
+    }
+
+    // as `finish`, but leave flushing the wrapped writer to the caller
+    pub(crate) fn end(mut self) -> Result<W, std::io::Error> {
+        let ends_line = self.ends_line();
+        let mut writer = self.writer.take().unwrap();
+        if ends_line {
+            writer.write_all(&self.separator)?;
+        }
+        Ok(writer)
+    }
+}
+
+impl<W: Write> Write for LineWrapper<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.column = wrapping_write(
+            buf,
+            buf.len(),
+            self.width,
+            self.column,
+            &self.prefix,
+            &self.separator,
+            self.writer.as_mut().unwrap(),
+        )?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.as_mut().unwrap().flush()
+    }
+}
+
+impl<W: Write> Drop for LineWrapper<W> {
+    fn drop(&mut self) {
+        let ends_line = self.ends_line();
+        if let Some(mut writer) = self.writer.take() {
+            if ends_line {
+                let _ = writer.write_all(&self.separator);
+            }
+        }
+    }
+}
+
+// write the first `len` bytes of `buffer`, starting at `current_col`, and return the new column
+fn wrapping_write(
+    buffer: &[u8],
+    len: usize,
+    wrap_col: Option<usize>,
+    mut current_col: usize,
+    prefix: &[u8],
+    line_ending: &[u8],
+    writer: &mut impl Write,
+) -> Result<usize, std::io::Error> {
+    let mut written: usize = 0;
+
+    // while there are more bytes to write
+    while written < len {
+        // if the line is full, end it and reset the column counter
+        if wrap_col == Some(current_col) {
+            writer.write_all(line_ending)?;
+            current_col = 0;
+        }
+
+        // start each line with the prefix
+        if current_col == 0 && !prefix.is_empty() {
+            writer.write_all(prefix)?;
+        }
+
+        // bytes to write this iteration is the min of bytes remaining in the line (if
+        // wrapping) and total bytes remaining
+        let n: usize = match wrap_col {
+            Some(line_length) => (line_length - current_col).min(len - written),
+            None => len - written,
+        };
+
+        // write the output and advance to the new column
+        writer.write_all(&buffer[written..written + n])?;
+        written += n;
+        current_col += n;
+    }
+
+    // return the new column
+    Ok(current_col)
+}
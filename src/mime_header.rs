@@ -0,0 +1,306 @@
+use std::io::{Read, Write};
+
+use crate::base64::{b64_decode, b64_encode};
+use crate::common::{hex_value, HEX_DIGITS};
+
+// maximum length of a single encoded-word (RFC 2047, section 2)
+const MAX_WORD_LENGTH: usize = 75;
+
+// separator placed between encoded-words (a folded header line)
+const FOLD: &[u8] = b"\r\n ";
+
+///
+/// Encoding used for the text of an RFC 2047 encoded-word
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MimeWordEncoding {
+    /// The "B" encoding: base-64 encoded text
+    B,
+    /// The "Q" encoding: quoted-printable-like encoded text
+    Q,
+}
+
+///
+/// Encode data as a sequence of RFC 2047 encoded-words
+///
+/// Encoded-words are limited to 75 characters and are separated by folding
+/// whitespace (CRLF followed by a space).  When the charset is UTF-8, multi-byte
+/// characters are never split across encoded-words.
+///
+/// # Arguments
+///
+/// * `reader` - Header text to encode
+/// * `writer` - Writer to which encoded-words will be written
+/// * `charset` - Charset of the header text (e.g. "UTF-8")
+/// * `encoding` - Whether to use the B or Q encoding
+///
+pub fn mime_header_encode(
+    reader: &mut impl Read,
+    writer: &mut impl Write,
+    charset: &str,
+    encoding: MimeWordEncoding,
+) -> Result<(), std::io::Error> {
+    // sanity-check parameters
+    if charset.is_empty()
+        || !charset
+            .bytes()
+            .all(|c| c.is_ascii_graphic() && c != b'?' && c != b'=')
+    {
+        return Err(std::io::Error::other("invalid charset"));
+    }
+
+    // calculate the space available for encoded text in each word
+    let overhead = charset.len() + 7;
+    let min_text = match encoding {
+        MimeWordEncoding::B => 4,
+        MimeWordEncoding::Q => 3,
+    };
+    if overhead + min_text > MAX_WORD_LENGTH {
+        return Err(std::io::Error::other("charset name too long"));
+    }
+    let max_text = MAX_WORD_LENGTH - overhead;
+
+    // words are split on character boundaries found by looking ahead, so read the whole
+    // header before encoding any of it
+    let mut input = Vec::new();
+    reader.read_to_end(&mut input)?;
+
+    // whether characters may span multiple bytes
+    let is_utf8 = charset.eq_ignore_ascii_case("utf-8") || charset.eq_ignore_ascii_case("utf8");
+
+    let mut start = 0;
+    while start < input.len() {
+        // take as many whole characters as will fit in the word
+        let mut end = start;
+        let mut text_len = 0;
+        while end < input.len() {
+            // find the end of the next character
+            let mut char_end = end + 1;
+            if is_utf8 {
+                while char_end < input.len() && (input[char_end] & 0xC0) == 0x80 {
+                    char_end += 1;
+                }
+            }
+
+            // calculate the encoded length if the character is included
+            let new_len = match encoding {
+                MimeWordEncoding::B => (char_end - start).div_ceil(3) * 4,
+                MimeWordEncoding::Q => {
+                    text_len
+                        + input[end..char_end]
+                            .iter()
+                            .map(|&c| q_len(c))
+                            .sum::<usize>()
+                }
+            };
+
+            // stop if it doesn't fit, unless the word would otherwise be empty
+            if new_len > max_text && end != start {
+                break;
+            }
+
+            text_len = new_len;
+            end = char_end;
+        }
+
+        // separate from the previous word
+        if start != 0 {
+            writer.write_all(FOLD)?;
+        }
+
+        // output the word
+        writer.write_all(b"=?")?;
+        writer.write_all(charset.as_bytes())?;
+        match encoding {
+            MimeWordEncoding::B => {
+                writer.write_all(b"?B?")?;
+                b64_encode(&mut &input[start..end], writer, None)?;
+            }
+            MimeWordEncoding::Q => {
+                writer.write_all(b"?Q?")?;
+                q_encode(&input[start..end], writer)?;
+            }
+        }
+        writer.write_all(b"?=")?;
+
+        start = end;
+    }
+
+    Ok(())
+}
+
+///
+/// Decode a header containing RFC 2047 encoded-words
+///
+/// Folded lines are unfolded, encoded-words are replaced by their decoded bytes
+/// (in their original charset), and whitespace between adjacent encoded-words is
+/// removed.  Anything that is not a well-formed encoded-word is passed through
+/// unchanged.
+///
+/// # Arguments
+///
+/// * `reader` - Header text to decode
+/// * `writer` - Writer to which decoded text will be written
+///
+pub fn mime_header_decode(
+    reader: &mut impl Read,
+    writer: &mut impl Write,
+) -> Result<(), std::io::Error> {
+    // whether whitespace is dropped depends on whether another encoded-word follows,
+    // possibly on a later folded line, so read the whole header before decoding any of it
+    let mut input = Vec::new();
+    reader.read_to_end(&mut input)?;
+    let input = unfold(&input);
+
+    // output buffer for the decoded header
+    let mut output = Vec::with_capacity(input.len());
+
+    // whitespace seen since the last encoded-word, which is dropped if another follows
+    let mut pending_whitespace: Option<usize> = None;
+
+    let mut i = 0;
+    while i < input.len() {
+        // try to decode an encoded-word at this position
+        if input[i..].starts_with(b"=?") {
+            if let Some((decoded, len)) = decode_word(&input[i..]) {
+                // drop any whitespace separating this word from the previous one
+                if let Some(ws_start) = pending_whitespace {
+                    output.truncate(ws_start);
+                }
+                output.extend_from_slice(&decoded);
+                i += len;
+                pending_whitespace = Some(output.len());
+                continue;
+            }
+        }
+
+        // anything other than whitespace separates encoded-words
+        if input[i] != b' ' && input[i] != b'\t' {
+            pending_whitespace = None;
+        }
+
+        output.push(input[i]);
+        i += 1;
+    }
+
+    writer.write_all(&output)
+}
+
+// length of a single byte in the Q encoding
+fn q_len(c: u8) -> usize {
+    if c == b' ' || is_q_safe(c) {
+        1
+    } else {
+        3
+    }
+}
+
+// whether a byte may appear literally in Q encoded text in any header context
+fn is_q_safe(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, b'!' | b'*' | b'+' | b'-' | b'/')
+}
+
+// encode bytes using the Q encoding
+fn q_encode(data: &[u8], writer: &mut impl Write) -> Result<(), std::io::Error> {
+    for &c in data {
+        if c == b' ' {
+            writer.write_all(b"_")?;
+        } else if is_q_safe(c) {
+            writer.write_all(&[c])?;
+        } else {
+            writer.write_all(&[
+                b'=',
+                HEX_DIGITS[(c >> 4) as usize],
+                HEX_DIGITS[(c & 0xF) as usize],
+            ])?;
+        }
+    }
+    Ok(())
+}
+
+// decode bytes using the Q encoding
+fn q_decode(text: &[u8]) -> Option<Vec<u8>> {
+    let mut output = Vec::with_capacity(text.len());
+    let mut i = 0;
+    while i < text.len() {
+        match text[i] {
+            b'_' => {
+                output.push(b' ');
+                i += 1;
+            }
+            b'=' => {
+                let hi = hex_value(*text.get(i + 1)?)?;
+                let lo = hex_value(*text.get(i + 2)?)?;
+                output.push((hi << 4) | lo);
+                i += 3;
+            }
+            c => {
+                output.push(c);
+                i += 1;
+            }
+        }
+    }
+    Some(output)
+}
+
+// decode the encoded-word at the start of `input`, returning the decoded bytes
+// and the length of the encoded-word
+fn decode_word(input: &[u8]) -> Option<(Vec<u8>, usize)> {
+    // split out the charset, encoding and text fields
+    let mut fields = input[2..].splitn(3, |&c| c == b'?');
+    let charset = fields.next()?;
+    let encoding = fields.next()?;
+    let rest = fields.next()?;
+    let text_len = rest.windows(2).position(|w| w == b"?=")?;
+    let text = &rest[..text_len];
+
+    // encoded-words may not contain whitespace, and the charset may not be empty
+    let is_token = |field: &[u8]| field.iter().all(|c| c.is_ascii_graphic());
+    if charset.is_empty() || !is_token(charset) || !is_token(text) {
+        return None;
+    }
+
+    // decode the text
+    let decoded = match encoding {
+        b"B" | b"b" => {
+            // tolerate missing padding
+            let mut padded = text.to_vec();
+            while !padded.len().is_multiple_of(4) {
+                padded.push(b'=');
+            }
+            let mut decoded = Vec::new();
+            b64_decode(&mut padded.as_slice(), &mut decoded, false).ok()?;
+            decoded
+        }
+        b"Q" | b"q" => q_decode(text)?,
+        _ => return None,
+    };
+
+    Some((
+        decoded,
+        2 + charset.len() + 1 + encoding.len() + 1 + text_len + 2,
+    ))
+}
+
+// remove line breaks that are followed by whitespace
+fn unfold(input: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(input.len());
+    let mut i = 0;
+    while i < input.len() {
+        let break_len = if input[i..].starts_with(b"\r\n") {
+            2
+        } else if input[i] == b'\n' {
+            1
+        } else {
+            0
+        };
+
+        if break_len != 0 && matches!(input.get(i + break_len), Some(b' ') | Some(b'\t')) {
+            i += break_len;
+        } else {
+            output.push(input[i]);
+            i += 1;
+        }
+    }
+    output
+}
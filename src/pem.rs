@@ -0,0 +1,273 @@
+use std::io::{BufRead, BufReader, Read, Write};
+
+use crate::config::B64Config;
+use crate::error::{relocate_error, Position};
+
+///
+/// A PEM block
+///
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PemBlock {
+    /// Label naming the type of data (e.g. "CERTIFICATE")
+    pub label: String,
+    /// RFC 1421 headers (e.g. "Proc-Type"), in order
+    pub headers: Vec<(String, String)>,
+    /// Decoded data, usually DER
+    pub data: Vec<u8>,
+}
+
+///
+/// Encode data as a PEM block
+///
+/// # Arguments
+///
+/// * `reader` - Data to encode
+/// * `writer` - Writer to which the PEM block will be written
+/// * `label` - Label naming the type of data (e.g. "CERTIFICATE")
+///
+pub fn pem_encode(
+    reader: &mut impl Read,
+    writer: &mut impl Write,
+    label: &str,
+) -> Result<(), std::io::Error> {
+    // sanity-check parameters, so the output can be decoded again
+    if !is_valid_label(label) {
+        return Err(std::io::Error::other("invalid PEM label"));
+    }
+
+    // with no headers, the data can be encoded as it is read
+    writeln!(writer, "-----BEGIN {}-----", label)?;
+    B64Config::PEM.encode(reader, writer)?;
+    writeln!(writer, "-----END {}-----", label)
+}
+
+///
+/// Encode a PEM block, including any headers
+///
+/// # Arguments
+///
+/// * `block` - Label, headers and data to encode
+/// * `writer` - Writer to which the PEM block will be written
+///
+pub fn pem_encode_block(block: &PemBlock, writer: &mut impl Write) -> Result<(), std::io::Error> {
+    // sanity-check parameters, so the output can be decoded again
+    if !is_valid_label(&block.label) {
+        return Err(std::io::Error::other("invalid PEM label"));
+    }
+    if !block
+        .headers
+        .iter()
+        .all(|(name, value)| is_valid_header(name, value))
+    {
+        return Err(std::io::Error::other("invalid PEM header"));
+    }
+
+    let mut output = Vec::new();
+    writeln!(output, "-----BEGIN {}-----", block.label)?;
+
+    // headers are separated from the data by a blank line
+    if !block.headers.is_empty() {
+        for (name, value) in &block.headers {
+            writeln!(output, "{}: {}", name, value)?;
+        }
+        writeln!(output)?;
+    }
+
+    B64Config::PEM.encode(&mut block.data.as_slice(), &mut output)?;
+    writeln!(output, "-----END {}-----", block.label)?;
+
+    writer.write_all(&output)
+}
+
+///
+/// Decode all PEM blocks
+///
+/// # Arguments
+///
+/// * `reader` - PEM reader
+///
+pub fn pem_decode(reader: &mut impl Read) -> Result<Vec<PemBlock>, std::io::Error> {
+    PemReader::new(BufReader::new(reader)).collect()
+}
+
+///
+/// Iterator over the PEM blocks in a stream
+///
+/// Text outside of blocks, such as the explanatory text that often precedes
+/// certificates, is skipped.  Errors in the base-64 data are reported as a
+/// `DecodeError` located within the whole stream.
+///
+pub struct PemReader<R: BufRead> {
+    reader: R,
+    // current line, and its position in the input
+    line: Vec<u8>,
+    line_number: usize,
+    line_offset: usize,
+}
+
+impl<R: BufRead> PemReader<R> {
+    ///
+    /// Wrap a reader
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - PEM reader
+    ///
+    pub fn new(reader: R) -> Self {
+        PemReader {
+            reader,
+            line: Vec::new(),
+            line_number: 0,
+            line_offset: 0,
+        }
+    }
+
+    // read the next line, returning false at the end of input
+    fn next_line(&mut self) -> Result<bool, std::io::Error> {
+        self.line_offset += self.line.len();
+        self.line.clear();
+        if self.reader.read_until(b'\n', &mut self.line)? == 0 {
+            return Ok(false);
+        }
+        self.line_number += 1;
+        Ok(true)
+    }
+
+    // error for an invalid line
+    fn invalid(&self, reason: &str) -> std::io::Error {
+        std::io::Error::other(format!(
+            "invalid PEM on line {}: {}",
+            self.line_number, reason
+        ))
+    }
+
+    // read the next block, if any
+    fn read_block(&mut self) -> Result<Option<PemBlock>, std::io::Error> {
+        // skip anything before the BEGIN line
+        let label = loop {
+            if !self.next_line()? {
+                return Ok(None);
+            }
+            if let Some(label) = boundary(&self.line, "BEGIN") {
+                break label;
+            }
+            if boundary(&self.line, "END").is_some() {
+                return Err(self.invalid("END line without BEGIN line"));
+            }
+        };
+        let begin_line = self.line_number;
+
+        // collect headers and encoded data up to the END line
+        let mut headers: Vec<(String, String)> = Vec::new();
+        let mut in_headers = false;
+        let mut encoded = Vec::new();
+        let mut encoded_start: Option<Position> = None;
+        loop {
+            if !self.next_line()? {
+                return Err(std::io::Error::other(format!(
+                    "invalid PEM on line {}: missing END line for {}",
+                    begin_line, label
+                )));
+            }
+            if let Some(end_label) = boundary(&self.line, "END") {
+                if end_label != label {
+                    return Err(
+                        self.invalid(&format!("BEGIN {} ended by END {}", label, end_label))
+                    );
+                }
+                break;
+            }
+            if boundary(&self.line, "BEGIN").is_some() {
+                return Err(self.invalid(&format!("missing END line for {}", label)));
+            }
+
+            let text = self.line.trim_ascii();
+
+            // headers, if any, come first and end with a blank line; base-64 never
+            // contains a colon
+            if encoded_start.is_none() && headers.is_empty() && text.contains(&b':') {
+                in_headers = true;
+            }
+            if in_headers {
+                if text.is_empty() {
+                    in_headers = false;
+                } else if self.line[0] == b' ' || self.line[0] == b'\t' {
+                    // continuation of a folded header
+                    let Some((_, value)) = headers.last_mut() else {
+                        return Err(self.invalid("continuation line without a preceding header"));
+                    };
+                    value.push_str(&String::from_utf8_lossy(self.line.trim_ascii_end()));
+                } else {
+                    let colon = text
+                        .iter()
+                        .position(|&c| c == b':')
+                        .ok_or_else(|| self.invalid("header missing ':'"))?;
+                    headers.push((
+                        String::from_utf8_lossy(text[0..colon].trim_ascii()).into_owned(),
+                        String::from_utf8_lossy(&text[colon + 1..]).into_owned(),
+                    ));
+                }
+                continue;
+            }
+
+            // remember where the encoded data starts, to locate any errors
+            if encoded_start.is_none() {
+                encoded_start = Some(Position {
+                    offset: self.line_offset,
+                    line: self.line_number,
+                    column: 1,
+                });
+            }
+            encoded.extend_from_slice(&self.line);
+        }
+
+        // decode the data, reporting errors where they are in the input
+        let data = B64Config::PEM
+            .decode_to_vec(&encoded)
+            .map_err(|error| relocate_error(error, encoded_start))?;
+
+        // unfolded header values may have gained surrounding whitespace
+        for (_, value) in headers.iter_mut() {
+            *value = value.trim().to_string();
+        }
+
+        Ok(Some(PemBlock {
+            label,
+            headers,
+            data,
+        }))
+    }
+}
+
+impl<R: BufRead> Iterator for PemReader<R> {
+    type Item = Result<PemBlock, std::io::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_block().transpose()
+    }
+}
+
+// label of a `-----BEGIN label-----` or `-----END label-----` line
+pub(crate) fn boundary(line: &[u8], kind: &str) -> Option<String> {
+    let label = line
+        .trim_ascii()
+        .strip_prefix(b"-----")?
+        .strip_prefix(kind.as_bytes())?
+        .strip_prefix(b" ")?
+        .strip_suffix(b"-----")?;
+    Some(String::from_utf8_lossy(label).into_owned())
+}
+
+// labels are printable ASCII, with no leading or trailing hyphens or spaces (RFC 7468, section 3)
+fn is_valid_label(label: &str) -> bool {
+    label.bytes().all(|c| c.is_ascii_graphic() || c == b' ')
+        && !label.starts_with(['-', ' '])
+        && !label.ends_with(['-', ' '])
+}
+
+// header names are printable ASCII other than ':', and values are a single line
+pub(crate) fn is_valid_header(name: &str, value: &str) -> bool {
+    !name.is_empty()
+        && name.bytes().all(|c| c.is_ascii_graphic() && c != b':')
+        && !value.bytes().any(|c| c == b'\r' || c == b'\n')
+}
@@ -0,0 +1,161 @@
+use std::io::{Read, Write};
+
+// consonants encode 4 bits each
+const CONSONANTS: &[u8; 16] = b"bdfghjklmnprstvz";
+
+// vowels encode 2 bits each
+const VOWELS: &[u8; 4] = b"aiou";
+
+///
+/// Encode data as proquints
+///
+/// Each 16-bit big-endian word is encoded as a pronounceable five letter
+/// consonant-vowel-consonant-vowel-consonant quint, and quints are separated by
+/// `-` (e.g. 127.0.0.1 is `lusab-babad`).
+///
+/// # Arguments
+///
+/// * `reader` - Data to encode, which must be a multiple of 2 bytes long
+/// * `writer` - Writer to which encoded data will be written
+///
+pub fn proquint_encode(
+    reader: &mut impl Read,
+    writer: &mut impl Write,
+) -> Result<(), std::io::Error> {
+    // read and write buffers
+    let mut read_buffer = [0; 4096];
+    let mut write_buffer = Vec::with_capacity(read_buffer.len() * 3);
+
+    // byte left over from the previous buffer, too few to encode
+    let mut leftover: Option<u8> = None;
+    let mut is_first = true;
+
+    loop {
+        // fill read buffer
+        let bytes_read = reader.read(&mut read_buffer)?;
+
+        // if out of data, exit loop
+        if bytes_read == 0 {
+            break;
+        }
+
+        // complete the leftover byte first
+        let mut buffer = &read_buffer[0..bytes_read];
+        if let Some(high) = leftover.take() {
+            push_quint(&mut write_buffer, [high, buffer[0]], &mut is_first);
+            buffer = &buffer[1..];
+        }
+
+        // encode each pair of bytes, keeping any leftover byte for the next buffer
+        let pairs = buffer.chunks_exact(2);
+        leftover = pairs.remainder().first().copied();
+        for pair in pairs {
+            push_quint(&mut write_buffer, [pair[0], pair[1]], &mut is_first);
+        }
+
+        writer.write_all(&write_buffer)?;
+        write_buffer.clear();
+    }
+
+    if leftover.is_some() {
+        return Err(std::io::Error::other(
+            "input length must be a multiple of 2 bytes",
+        ));
+    }
+    Ok(())
+}
+
+///
+/// Decode proquints
+///
+/// Quints may be separated by `-` or whitespace and are matched
+/// case-insensitively.  Every quint is validated.
+///
+/// # Arguments
+///
+/// * `reader` - Proquint reader
+/// * `writer` - Writer to which decoded data will be written
+///
+pub fn proquint_decode(
+    reader: &mut impl Read,
+    writer: &mut impl Write,
+) -> Result<(), std::io::Error> {
+    // read and write buffers
+    let mut read_buffer = [0; 4096];
+    let mut write_buffer = Vec::with_capacity(read_buffer.len() / 3 + 2);
+
+    // quint being read, which may continue into the next buffer
+    let mut quint = Vec::new();
+
+    loop {
+        // fill read buffer
+        let bytes_read = reader.read(&mut read_buffer)?;
+
+        // decode each complete quint
+        for &c in &read_buffer[0..bytes_read] {
+            if c == b'-' || c.is_ascii_whitespace() {
+                push_word(&mut write_buffer, &quint)?;
+                quint.clear();
+            } else {
+                quint.push(c);
+            }
+        }
+
+        // if out of data, the last quint is complete
+        if bytes_read == 0 {
+            push_word(&mut write_buffer, &quint)?;
+            return writer.write_all(&write_buffer);
+        }
+
+        writer.write_all(&write_buffer)?;
+        write_buffer.clear();
+    }
+}
+
+// append the quint encoding a big-endian pair of bytes, separated from any before it
+fn push_quint(buffer: &mut Vec<u8>, pair: [u8; 2], is_first: &mut bool) {
+    if !*is_first {
+        buffer.push(b'-');
+    }
+    *is_first = false;
+
+    let word = u16::from_be_bytes(pair);
+    buffer.push(CONSONANTS[(word >> 12) as usize]);
+    buffer.push(VOWELS[((word >> 10) & 0x3) as usize]);
+    buffer.push(CONSONANTS[((word >> 6) & 0xF) as usize]);
+    buffer.push(VOWELS[((word >> 4) & 0x3) as usize]);
+    buffer.push(CONSONANTS[(word & 0xF) as usize]);
+}
+
+// append the big-endian bytes of the word a quint encodes, if not empty
+fn push_word(buffer: &mut Vec<u8>, quint: &[u8]) -> Result<(), std::io::Error> {
+    if quint.is_empty() {
+        return Ok(());
+    }
+    let word = decode_quint(quint).ok_or_else(|| {
+        std::io::Error::other(format!(
+            "invalid proquint '{}'",
+            String::from_utf8_lossy(quint)
+        ))
+    })?;
+    buffer.extend_from_slice(&word.to_be_bytes());
+    Ok(())
+}
+
+// decode a single quint into a 16-bit word
+fn decode_quint(quint: &[u8]) -> Option<u16> {
+    if quint.len() != 5 {
+        return None;
+    }
+
+    let mut word: u16 = 0;
+    for (i, &c) in quint.iter().enumerate() {
+        let c = c.to_ascii_lowercase();
+        word = if i % 2 == 0 {
+            (word << 4) | CONSONANTS.iter().position(|&s| s == c)? as u16
+        } else {
+            (word << 2) | VOWELS.iter().position(|&s| s == c)? as u16
+        };
+    }
+    Some(word)
+}
@@ -0,0 +1,215 @@
+use std::io::{BufRead, BufReader, Read, Write};
+
+use crate::common::{parse_hex_bytes, push_hex_byte, write_image, HEX_DIGITS};
+
+///
+/// Encode a binary image as Motorola S-records
+///
+/// The narrowest address size able to hold every address in the image is
+/// used: S1/S9 records for 16-bit addresses, S2/S8 for 24-bit and S3/S7 for
+/// 32-bit.  Output consists of an S0 header, data records, an S5/S6 record
+/// count and a termination record whose start address is the base address.
+///
+/// # Arguments
+///
+/// * `reader` - Binary image to encode
+/// * `writer` - Writer to which records will be written
+/// * `base_address` - Address of the first byte of the image
+/// * `record_length` - Maximum number of data bytes per record
+///
+pub fn srec_encode(
+    reader: &mut impl Read,
+    writer: &mut impl Write,
+    base_address: u32,
+    record_length: usize,
+) -> Result<(), std::io::Error> {
+    // the address size depends on where the image ends, so read ahead only until the image
+    // is known to end within the narrowest size, or to need 32-bit addresses
+    let mut lookahead = Vec::new();
+    let (address_size, data_type, termination_type) = loop {
+        let end_address = base_address as u64 + lookahead.len().max(1) as u64 - 1;
+        let (limit, size) = if end_address <= 0xFFFF {
+            (0xFFFF, (2, b'1', b'9'))
+        } else if end_address <= 0xFF_FFFF {
+            (0xFF_FFFF, (3, b'2', b'8'))
+        } else {
+            break (4, b'3', b'7');
+        };
+
+        // read up to one byte past the limit, so the image ends within it if that isn't reached
+        let wanted = limit + 2 - base_address as u64 - lookahead.len() as u64;
+        let n = reader.by_ref().take(wanted).read_to_end(&mut lookahead)?;
+        if (n as u64) < wanted {
+            break size;
+        }
+    };
+
+    // sanity-check parameters, the byte count covers address, data and checksum
+    if record_length == 0 || record_length + address_size + 1 > 255 {
+        return Err(std::io::Error::other("invalid record length"));
+    }
+
+    let mut line = Vec::new();
+
+    // output header
+    write_record(&mut line, b'0', 0, 2, &[], writer)?;
+
+    // output data records, reading a record at a time after the data read ahead
+    let mut input = lookahead.as_slice().chain(reader);
+    let mut data = Vec::with_capacity(record_length);
+    let mut record_count: u32 = 0;
+    let mut address = base_address as u64;
+    loop {
+        data.clear();
+        input
+            .by_ref()
+            .take(record_length as u64)
+            .read_to_end(&mut data)?;
+        if data.is_empty() {
+            break;
+        }
+        if address + data.len() as u64 - 1 > u32::MAX as u64 {
+            return Err(std::io::Error::other("image exceeds 32-bit address space"));
+        }
+        write_record(
+            &mut line,
+            data_type,
+            address as u32,
+            address_size,
+            &data,
+            writer,
+        )?;
+        record_count += 1;
+        address += data.len() as u64;
+    }
+
+    // output record count
+    if record_count <= 0xFFFF {
+        write_record(&mut line, b'5', record_count, 2, &[], writer)?;
+    } else {
+        write_record(&mut line, b'6', record_count, 3, &[], writer)?;
+    }
+
+    // output termination
+    write_record(
+        &mut line,
+        termination_type,
+        base_address,
+        address_size,
+        &[],
+        writer,
+    )
+}
+
+///
+/// Decode Motorola S-records into a binary image
+///
+/// The image starts at the lowest address present in the records, and gaps
+/// between records are filled with 0xFF.  Record counts, when present, are
+/// checked against the number of data records.
+///
+/// # Arguments
+///
+/// * `reader` - S-records reader
+/// * `writer` - Writer to which the binary image will be written
+///
+pub fn srec_decode(reader: &mut impl Read, writer: &mut impl Write) -> Result<(), std::io::Error> {
+    // S-records may arrive out of address order, so collect the data of each before
+    // assembling the image
+    let mut segments: Vec<(u64, Vec<u8>)> = Vec::new();
+    let mut reached_end = false;
+
+    for (line_index, line) in BufReader::new(reader).split(b'\n').enumerate() {
+        let line = line?;
+        let line = line.trim_ascii();
+        if line.is_empty() {
+            continue;
+        }
+
+        // nothing may follow the termination record
+        let invalid = |reason: &str| {
+            std::io::Error::other(format!(
+                "invalid record on line {}: {}",
+                line_index + 1,
+                reason
+            ))
+        };
+        if reached_end {
+            return Err(invalid("data after termination record"));
+        }
+
+        // parse the record
+        if line.len() < 2 || line[0] != b'S' {
+            return Err(invalid("missing start code"));
+        }
+        let record_type = line[1];
+        let record = parse_hex_bytes(&line[2..]).ok_or_else(|| invalid("invalid hex digits"))?;
+        let address_size = match record_type {
+            b'0' | b'1' | b'5' | b'9' => 2,
+            b'2' | b'6' | b'8' => 3,
+            b'3' | b'7' => 4,
+            _ => return Err(invalid("unknown record type")),
+        };
+        if record.len() < address_size + 2 || record.len() != record[0] as usize + 1 {
+            return Err(invalid("invalid length"));
+        }
+
+        // checksum is the one's complement of the sum of all record bytes
+        if record.iter().fold(0u8, |sum, &b| sum.wrapping_add(b)) != 0xFF {
+            return Err(invalid("checksum mismatch"));
+        }
+        let address = record[1..1 + address_size]
+            .iter()
+            .fold(0u64, |address, &b| (address << 8) | b as u64);
+        let data = &record[1 + address_size..record.len() - 1];
+
+        match record_type {
+            // header contents are informational only
+            b'0' => {}
+            b'1' | b'2' | b'3' => segments.push((address, data.to_vec())),
+            b'5' | b'6' => {
+                if address != segments.len() as u64 {
+                    return Err(invalid("record count mismatch"));
+                }
+            }
+            _ => reached_end = true,
+        }
+    }
+
+    if !reached_end {
+        return Err(std::io::Error::other("missing termination record"));
+    }
+
+    write_image(segments, writer)
+}
+
+// output a single record
+fn write_record(
+    line: &mut Vec<u8>,
+    record_type: u8,
+    address: u32,
+    address_size: usize,
+    data: &[u8],
+    writer: &mut impl Write,
+) -> Result<(), std::io::Error> {
+    let count = (address_size + data.len() + 1) as u8;
+    let address_bytes = &address.to_be_bytes()[4 - address_size..];
+
+    // checksum is the one's complement of the sum of all record bytes
+    let sum = address_bytes
+        .iter()
+        .chain(data)
+        .fold(count, |sum, &b| sum.wrapping_add(b));
+
+    line.clear();
+    line.push(b'S');
+    line.push(record_type);
+    push_hex_byte(line, count, HEX_DIGITS);
+    for &b in address_bytes.iter().chain(data) {
+        push_hex_byte(line, b, HEX_DIGITS);
+    }
+    push_hex_byte(line, !sum, HEX_DIGITS);
+    line.push(b'\n');
+
+    writer.write_all(line)
+}
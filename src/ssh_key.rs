@@ -0,0 +1,266 @@
+use std::io::{BufRead, BufReader, Read};
+
+use crate::common::{hex_string, LOWER_HEX_DIGITS};
+use crate::config::{B64Config, Padding};
+use crate::digest::{md5, sha256};
+use crate::error::{relocate_error, DecodeError, Position};
+
+// fingerprints are unpadded base-64, as printed by ssh-keygen
+const FINGERPRINT: B64Config = B64Config::STANDARD.padding(Padding::Forbidden);
+
+///
+/// An SSH public key, as found in `authorized_keys` and `.pub` files
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SshPublicKey {
+    /// Options preceding the key in `authorized_keys` files (e.g. "no-pty")
+    pub options: Option<String>,
+    /// Key type (e.g. "ssh-ed25519")
+    pub key_type: String,
+    /// Key size in bits, if the key type is known
+    pub bits: Option<usize>,
+    /// Key in SSH wire format
+    pub blob: Vec<u8>,
+    /// Comment following the key, if any
+    pub comment: Option<String>,
+}
+
+impl SshPublicKey {
+    ///
+    /// OpenSSH-style SHA-256 fingerprint (e.g. "SHA256:...")
+    ///
+    pub fn fingerprint_sha256(&self) -> String {
+        let digest = sha256(&self.blob);
+        format!("SHA256:{}", FINGERPRINT.encode_to_string(&digest).unwrap())
+    }
+
+    ///
+    /// OpenSSH-style MD5 fingerprint (e.g. "MD5:12:34:...")
+    ///
+    pub fn fingerprint_md5(&self) -> String {
+        let digest = md5(&self.blob);
+        format!("MD5:{}", hex_string(&digest, LOWER_HEX_DIGITS, ":"))
+    }
+
+    ///
+    /// Short name of the key's algorithm, as printed by ssh-keygen (e.g. "ED25519")
+    ///
+    pub fn algorithm(&self) -> &str {
+        match self.key_type.as_str() {
+            "ssh-rsa" => "RSA",
+            "ssh-dss" => "DSA",
+            "ssh-ed25519" => "ED25519",
+            "sk-ssh-ed25519@openssh.com" => "ED25519-SK",
+            "sk-ecdsa-sha2-nistp256@openssh.com" => "ECDSA-SK",
+            key_type if key_type.starts_with("ecdsa-sha2-") => "ECDSA",
+            key_type => key_type,
+        }
+    }
+}
+
+///
+/// Parse a public key line
+///
+/// # Arguments
+///
+/// * `line` - Key line, optionally preceded by `authorized_keys` options
+///
+pub fn ssh_key_parse(line: &str) -> Result<SshPublicKey, std::io::Error> {
+    let leading = line.len() - line.trim_start().len();
+    let line = line.trim();
+    let fields = split_fields(line);
+
+    // options may precede the key type, and contain quoted spaces
+    let type_index = fields
+        .iter()
+        .position(|(_, field)| is_key_type(field))
+        .ok_or_else(|| std::io::Error::other("missing key type"))?;
+    let options = match type_index {
+        0 => None,
+        1 => Some(fields[0].1.to_string()),
+        _ => return Err(std::io::Error::other("unexpected text before key type")),
+    };
+    let key_type = fields[type_index].1;
+    let &(encoded_start, encoded) = fields
+        .get(type_index + 1)
+        .ok_or_else(|| std::io::Error::other("missing key data"))?;
+
+    // comments may contain spaces
+    let comment = fields
+        .get(type_index + 2)
+        .map(|&(start, _)| line[start..].to_string());
+
+    // report errors in the key data where they are in the line
+    let offset = leading + encoded_start;
+    let blob = B64Config::STANDARD
+        .decode_to_vec(encoded.as_bytes())
+        .map_err(|error| {
+            let start = Position {
+                offset,
+                line: 1,
+                column: offset + 1,
+            };
+            relocate_error(error, Some(start))
+        })?;
+    let bits = parse_blob(key_type, &blob)?;
+
+    Ok(SshPublicKey {
+        options,
+        key_type: key_type.to_string(),
+        bits,
+        blob,
+        comment,
+    })
+}
+
+///
+/// Parse all public keys, skipping blank lines and comments
+///
+/// Errors in base-64 key data are reported as a `DecodeError` located within
+/// the whole input.
+///
+/// # Arguments
+///
+/// * `reader` - Reader of key lines
+///
+pub fn ssh_keys_parse(reader: &mut impl Read) -> Result<Vec<SshPublicKey>, std::io::Error> {
+    // keys are parsed one line at a time
+    let mut reader = BufReader::new(reader);
+    let mut line = String::new();
+    let mut keys = Vec::new();
+    let mut offset = 0;
+    for line_index in 0.. {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            break;
+        }
+        let line_start = Position {
+            offset,
+            line: line_index + 1,
+            column: 1,
+        };
+        offset += line.len();
+
+        let text = line.trim();
+        if text.is_empty() || text.starts_with('#') {
+            continue;
+        }
+
+        // base-64 errors are located within the whole input, others by line
+        let key = ssh_key_parse(&line).map_err(|error| {
+            if error
+                .get_ref()
+                .is_some_and(|inner| inner.is::<DecodeError>())
+            {
+                relocate_error(error, Some(line_start))
+            } else {
+                std::io::Error::other(format!("invalid key on line {}: {}", line_index + 1, error))
+            }
+        })?;
+        keys.push(key);
+    }
+    Ok(keys)
+}
+
+// whether or not a field is a key type, rather than options
+fn is_key_type(field: &str) -> bool {
+    ["ssh-", "ecdsa-", "sk-"]
+        .iter()
+        .any(|prefix| field.starts_with(prefix))
+        && !field.contains(['=', ',', '"'])
+}
+
+// split on whitespace outside of double quotes, returning each field and its offset
+fn split_fields(line: &str) -> Vec<(usize, &str)> {
+    let mut fields = Vec::new();
+    let mut start = None;
+    let mut quoted = false;
+    let mut escaped = false;
+    for (index, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if let Some(start) = start.take() {
+                    fields.push((start, &line[start..index]));
+                }
+                continue;
+            }
+            _ => (),
+        }
+        start.get_or_insert(index);
+    }
+    if let Some(start) = start {
+        fields.push((start, &line[start..]));
+    }
+    fields
+}
+
+// check the key's type, and find its size in bits, if known
+fn parse_blob(key_type: &str, blob: &[u8]) -> Result<Option<usize>, std::io::Error> {
+    let mut reader = WireReader(blob);
+    let blob_type = reader.string()?;
+    if blob_type != key_type.as_bytes() {
+        return Err(std::io::Error::other(format!(
+            "key type {} does not match key data type {}",
+            key_type,
+            String::from_utf8_lossy(blob_type)
+        )));
+    }
+
+    let bits = match key_type {
+        // e, then modulus n
+        "ssh-rsa" => {
+            reader.string()?;
+            Some(mpint_bits(reader.string()?))
+        }
+        // p, q, g, then y
+        "ssh-dss" => Some(mpint_bits(reader.string()?)),
+        "ssh-ed25519" | "sk-ssh-ed25519@openssh.com" => {
+            let key = reader.string()?;
+            if key.len() != 32 {
+                return Err(std::io::Error::other("invalid Ed25519 key length"));
+            }
+            Some(256)
+        }
+        // the curve is named explicitly, as well as in the type
+        key_type if key_type.starts_with("ecdsa-sha2-") || key_type.starts_with("sk-ecdsa-") => {
+            match reader.string()? {
+                b"nistp256" => Some(256),
+                b"nistp384" => Some(384),
+                b"nistp521" => Some(521),
+                _ => None,
+            }
+        }
+        _ => None,
+    };
+    Ok(bits)
+}
+
+// size of a big-endian, two's complement integer, ignoring leading zeros
+fn mpint_bits(mpint: &[u8]) -> usize {
+    match mpint.iter().position(|&byte| byte != 0) {
+        Some(first) => (mpint.len() - first) * 8 - mpint[first].leading_zeros() as usize,
+        None => 0,
+    }
+}
+
+// reader of length-prefixed strings (RFC 4251, section 5)
+struct WireReader<'a>(&'a [u8]);
+
+impl<'a> WireReader<'a> {
+    fn string(&mut self) -> Result<&'a [u8], std::io::Error> {
+        let truncated = || std::io::Error::other("truncated key data");
+        let len = self
+            .0
+            .get(0..4)
+            .map(|len| u32::from_be_bytes(len.try_into().unwrap()) as usize)
+            .ok_or_else(truncated)?;
+        // the length is untrusted, so may overflow on 32-bit targets
+        let end = 4usize.checked_add(len).ok_or_else(truncated)?;
+        let string = self.0.get(4..end).ok_or_else(truncated)?;
+        self.0 = &self.0[end..];
+        Ok(string)
+    }
+}
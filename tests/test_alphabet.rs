@@ -0,0 +1,185 @@
+use base_util::*;
+use std::io::{ErrorKind, Read, Write};
+
+// a shuffled alphabet, validated at compile time
+const SHUFFLED: B64Alphabet =
+    match Alphabet::new(b"zyxwvutsrqponmlkjihgfedcbaZYXWVUTSRQPONMLKJIHGFEDCBA9876543210_.") {
+        Ok(alphabet) => alphabet,
+        Err(_) => panic!("invalid alphabet"),
+    };
+
+fn encode_with(input: &[u8], alphabet: B64Alphabet) -> Vec<u8> {
+    let mut output = Vec::new();
+    let mut encoder = B64Encoder::with_config(&B64Config::STANDARD.alphabet(alphabet)).unwrap();
+    encoder.update(input, &mut output);
+    encoder.finalize(&mut output);
+    output
+}
+
+fn decode_with(input: &[u8], alphabet: B64Alphabet) -> Result<Vec<u8>, std::io::Error> {
+    let mut output = Vec::new();
+    let mut decoder = B64Decoder::with_config(&B64Config::STANDARD.alphabet(alphabet));
+    decoder.update(input, &mut output)?;
+    decoder.finalize(&mut output)?;
+    Ok(output)
+}
+
+#[test]
+fn test_standard_alphabets() {
+    assert_eq!(
+        B64Alphabet::STANDARD.symbols(),
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/"
+    );
+    assert_eq!(
+        B64Alphabet::URL_SAFE.symbols(),
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_"
+    );
+    assert_eq!(
+        B32Alphabet::STANDARD.symbols(),
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567"
+    );
+    assert_eq!(
+        B32Alphabet::EXTENDED_HEX.symbols(),
+        b"0123456789ABCDEFGHIJKLMNOPQRSTUV"
+    );
+    assert_eq!(
+        Alphabet::new(b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567"),
+        Ok(B32Alphabet::STANDARD)
+    );
+}
+
+#[test]
+fn test_invalid_alphabets() {
+    let mut symbols = *B64Alphabet::STANDARD.symbols();
+    symbols[63] = b'A';
+    assert_eq!(
+        Alphabet::new(&symbols),
+        Err(AlphabetError::DuplicateSymbol(b'A'))
+    );
+    symbols[63] = b'=';
+    assert_eq!(Alphabet::new(&symbols), Err(AlphabetError::PaddingSymbol));
+    symbols[63] = b' ';
+    assert_eq!(
+        Alphabet::new(&symbols),
+        Err(AlphabetError::InvalidSymbol(b' '))
+    );
+    symbols[63] = b'\n';
+    assert_eq!(
+        Alphabet::new(&symbols),
+        Err(AlphabetError::InvalidSymbol(b'\n'))
+    );
+    symbols[63] = 0xC3;
+    assert_eq!(
+        Alphabet::new(&symbols),
+        Err(AlphabetError::InvalidSymbol(0xC3))
+    );
+
+    // errors convert for use with `?`
+    let error: std::io::Error = AlphabetError::DuplicateSymbol(b'A').into();
+    assert_eq!(error.kind(), ErrorKind::InvalidInput);
+    assert_eq!(error.to_string(), "duplicate alphabet symbol 'A'");
+    assert_eq!(
+        AlphabetError::InvalidSymbol(0xC3).to_string(),
+        "invalid alphabet symbol 0xC3"
+    );
+}
+
+#[test]
+fn test_url_safe() {
+    assert_eq!(encode_with(b"\xFB\xFF\xBF", B64Alphabet::URL_SAFE), b"-_-_");
+    assert_eq!(
+        decode_with(b"-_-_", B64Alphabet::URL_SAFE).unwrap(),
+        b"\xFB\xFF\xBF"
+    );
+    assert_eq!(
+        decode_with(b"+/+/", B64Alphabet::STANDARD).unwrap(),
+        b"\xFB\xFF\xBF"
+    );
+
+    // symbols of the standard alphabet are garbage
+    let error = decode_with(b"+/+/", B64Alphabet::URL_SAFE).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+    assert!(matches!(
+        error.get_ref().unwrap().downcast_ref::<DecodeError>(),
+        Some(DecodeError::InvalidByte { byte: b'+', .. })
+    ));
+}
+
+#[test]
+fn test_custom_alphabet() {
+    let data: Vec<u8> = (0..=255).collect();
+    for len in 0..data.len() {
+        let encoded = encode_with(&data[0..len], SHUFFLED);
+        assert!(encoded
+            .iter()
+            .all(|c| SHUFFLED.symbols().contains(c) || *c == b'='));
+        assert_eq!(decode_with(&encoded, SHUFFLED).unwrap(), &data[0..len]);
+    }
+    assert_eq!(encode_with(b"Man", SHUFFLED), b"gduF");
+    assert_eq!(encode_with(b"M", SHUFFLED), b"gj==");
+
+    // whitespace is still skipped, and trailing bits still checked
+    let mut output = Vec::new();
+    let mut decoder = B64Decoder::with_config(&B64Config::STANDARD.alphabet(SHUFFLED).strict(true));
+    decoder.update(b"gj\n==\n", &mut output).unwrap();
+    decoder.finalize(&mut output).unwrap();
+    assert_eq!(output, b"M");
+    assert!(decode_with(b"gi==", SHUFFLED).is_ok());
+    let mut decoder = B64Decoder::with_config(&B64Config::STANDARD.alphabet(SHUFFLED).strict(true));
+    assert!(decoder.update(b"gi==", &mut output).is_err());
+}
+
+#[test]
+fn test_custom_alphabet_streams() {
+    let data: Vec<u8> = (0..100000).map(|i| (i * 7 % 256) as u8).collect();
+
+    let mut writer = B64EncoderWriter::with_config(
+        Vec::new(),
+        &B64Config::STANDARD.wrap(Some(76)).alphabet(SHUFFLED),
+    )
+    .unwrap();
+    writer.write_all(&data).unwrap();
+    let encoded = writer.finish().unwrap();
+
+    let mut reader =
+        B64DecoderReader::with_config(encoded.as_slice(), &B64Config::STANDARD.alphabet(SHUFFLED));
+    let mut decoded = Vec::new();
+    reader.read_to_end(&mut decoded).unwrap();
+    assert_eq!(decoded, data);
+
+    let mut writer = B32EncoderWriter::with_config(
+        Vec::new(),
+        &B32Config::STANDARD
+            .wrap(Some(76))
+            .alphabet(B32Alphabet::EXTENDED_HEX),
+    )
+    .unwrap();
+    writer.write_all(&data).unwrap();
+    let encoded = writer.finish().unwrap();
+    assert!(encoded.starts_with(b"003GS58S"));
+
+    let mut reader = B32DecoderReader::with_config(
+        encoded.as_slice(),
+        &B32Config::STANDARD.alphabet(B32Alphabet::EXTENDED_HEX),
+    );
+    let mut decoded = Vec::new();
+    reader.read_to_end(&mut decoded).unwrap();
+    assert_eq!(decoded, data);
+}
+
+#[test]
+fn test_base32_alphabets() {
+    let mut output = Vec::new();
+    let mut encoder =
+        B32Encoder::with_config(&B32Config::STANDARD.alphabet(B32Alphabet::EXTENDED_HEX)).unwrap();
+    encoder.update(b"foobar", &mut output);
+    encoder.finalize(&mut output);
+    assert_eq!(output, b"CPNMUOJ1E8======");
+
+    let lowercase = Alphabet::new(b"abcdefghijklmnopqrstuvwxyz234567").unwrap();
+    let mut output = Vec::new();
+    let mut decoder = B32Decoder::with_config(&B32Config::STANDARD.alphabet(lowercase));
+    decoder.update(b"mzxw6ytboi======", &mut output).unwrap();
+    decoder.finalize(&mut output).unwrap();
+    assert_eq!(output, b"foobar");
+}
@@ -1,5 +1,5 @@
 use base_util::*;
-use std::io::{BufReader, BufWriter};
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Write};
 
 fn encode(input: &[u8], wrap: Option<usize>) -> Result<Vec<u8>, std::io::Error> {
     let output = Vec::new();
@@ -89,3 +89,364 @@ fn test_ignore_invalid_char() {
 fn test_invalid_wrap() {
     test_encode_err(b"hello world", Some(0));
 }
+
+fn decode_error(input: &[u8], ignore_garbage: bool) -> DecodeError {
+    let err = decode(input, ignore_garbage).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    *err.get_ref()
+        .unwrap()
+        .downcast_ref::<DecodeError>()
+        .unwrap()
+}
+
+#[test]
+fn test_error_positions() {
+    assert_eq!(
+        decode_error(b"NBSWY3DP\nFQQH!O33\n", false)
+            .position()
+            .column,
+        5
+    );
+    assert!(matches!(
+        decode_error(b"NBSWY3==", false),
+        DecodeError::InvalidPadding { .. }
+    ));
+    assert!(matches!(
+        decode_error(b"NBSWY3DPFQ======NBSWY3DP", false),
+        DecodeError::DataAfterPadding { .. }
+    ));
+    assert!(matches!(
+        decode_error(b"NBSWY3DPFQ", false),
+        DecodeError::TruncatedInput { .. }
+    ));
+}
+
+#[test]
+fn test_error_position_across_buffers() {
+    // an invalid byte completing a word that started in the previous buffer
+    let mut input = b"\n".to_vec();
+    b32_encode(&mut &[0; 50000][..], &mut input, None).unwrap();
+    input[65536] = b'!';
+    assert_eq!(decode_error(&input, false).position().offset, 65536);
+}
+
+#[test]
+fn test_first_error_whatever_the_buffer_size() {
+    // the forbidden padding comes before the invalid byte, however the word is split
+    let config = B32Config::STANDARD
+        .padding(Padding::Forbidden)
+        .decode_policy(DecodePolicy::StopAtPadding);
+    let input = b"22X4O=4!M2VE";
+    let expected = DecodeError::InvalidPadding {
+        position: Position {
+            offset: 5,
+            line: 1,
+            column: 6,
+        },
+    };
+    let error = config.decode_to_vec(input).unwrap_err();
+    assert_eq!(error.get_ref().unwrap().downcast_ref(), Some(&expected));
+    for buffer_size in 1..=input.len() {
+        let error = config
+            .buffer_size(buffer_size)
+            .decode(&mut &input[..], &mut Vec::new())
+            .unwrap_err();
+        assert_eq!(error.get_ref().unwrap().downcast_ref(), Some(&expected));
+    }
+}
+
+#[test]
+fn test_decoder_reader() {
+    let mut output = Vec::new();
+    B32DecoderReader::new(&b"NBSWY3DPFQQHO33S\nNRSCC===\n"[..], false)
+        .read_to_end(&mut output)
+        .unwrap();
+    assert_eq!(output, b"hello, world!");
+
+    // reads smaller than the decoded data return it piece by piece
+    let mut reader = B32DecoderReader::new(&b"MFRGGZDFMY======"[..], false);
+    let mut buf = [0; 4];
+    assert_eq!(reader.read(&mut buf).unwrap(), 4);
+    assert_eq!(&buf, b"abcd");
+    assert_eq!(reader.read(&mut buf).unwrap(), 2);
+    assert_eq!(&buf[0..2], b"ef");
+    assert_eq!(reader.read(&mut buf).unwrap(), 0);
+}
+
+#[test]
+fn test_decoder_reader_large() {
+    let data: Vec<u8> = (0..200000).map(|i| (i * 7 % 251) as u8).collect();
+    let mut input = Vec::new();
+    b32_encode(&mut &data[..], &mut input, Some(76)).unwrap();
+
+    let mut output = Vec::new();
+    B32DecoderReader::new(&input[..], false)
+        .read_to_end(&mut output)
+        .unwrap();
+    assert_eq!(output, data);
+}
+
+#[test]
+fn test_decoder_reader_errors() {
+    let mut output = Vec::new();
+    let error = B32DecoderReader::new(&b"MFRGG\nZ!FMY======"[..], false)
+        .read_to_end(&mut output)
+        .unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+    assert!(matches!(
+        error.get_ref().unwrap().downcast_ref::<DecodeError>(),
+        Some(DecodeError::InvalidByte { byte: b'!', .. })
+    ));
+
+    let error = B32DecoderReader::new(&b"MFRGGZD"[..], false)
+        .read_to_end(&mut output)
+        .unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_encoder_writer() {
+    // leftover bytes are carried between writes
+    let mut writer = B32EncoderWriter::new(Vec::new(), None).unwrap();
+    writer.write_all(b"hel").unwrap();
+    writer.write_all(b"lo, w").unwrap();
+    writer.write_all(b"orld!").unwrap();
+    assert_eq!(writer.finish().unwrap(), b"NBSWY3DPFQQHO33SNRSCC===");
+
+    // the wrap column is carried between writes
+    let mut writer = B32EncoderWriter::new(Vec::new(), Some(16)).unwrap();
+    for &b in b"hello, world!" {
+        writer.write_all(&[b]).unwrap();
+    }
+    assert_eq!(writer.finish().unwrap(), b"NBSWY3DPFQQHO33S\nNRSCC===\n");
+
+    // dropping the writer finishes it
+    let mut output = Vec::new();
+    {
+        let mut writer = B32EncoderWriter::new(&mut output, None).unwrap();
+        writer.write_all(b"ab").unwrap();
+    }
+    assert_eq!(output, b"MFRA====");
+
+    assert!(B32EncoderWriter::new(Vec::new(), Some(0)).is_err());
+}
+
+#[test]
+fn test_encoder_writer_large() {
+    let data: Vec<u8> = (0..200000).map(|i| (i * 7 % 251) as u8).collect();
+    let mut expected = Vec::new();
+    b32_encode(&mut &data[..], &mut expected, Some(76)).unwrap();
+
+    let mut writer = B32EncoderWriter::new(Vec::new(), Some(76)).unwrap();
+    for chunk in data.chunks(1001) {
+        writer.write_all(chunk).unwrap();
+    }
+    assert_eq!(writer.finish().unwrap(), expected);
+}
+
+#[test]
+fn test_incremental_encoder() {
+    let mut encoder = B32Encoder::new(Some(16)).unwrap();
+    let mut output = Vec::new();
+    for fragment in [&b"h"[..], b"ello", b"", b", wor", b"ld!"] {
+        encoder.update(fragment, &mut output);
+    }
+    encoder.finalize(&mut output);
+    assert_eq!(output, b"NBSWY3DPFQQHO33S\nNRSCC===\n");
+
+    assert!(B32Encoder::new(Some(0)).is_err());
+}
+
+#[test]
+fn test_incremental_decoder() {
+    let mut decoder = B32Decoder::new(false);
+    let mut output = Vec::new();
+    for fragment in [&b"N"[..], b"BSWY3DPFQ\nQ", b"", b"HO33SNRSCC", b"===\n"] {
+        decoder.update(fragment, &mut output).unwrap();
+    }
+    decoder.finalize(&mut output).unwrap();
+    assert_eq!(output, b"hello, world!");
+
+    // errors leave earlier output intact
+    let mut decoder = B32Decoder::new(false);
+    let mut output = Vec::new();
+    decoder.update(b"MFRGGZDF\nMZ", &mut output).unwrap();
+    assert!(decoder.update(b"!", &mut output).is_err());
+    assert_eq!(output, b"abcde");
+
+    // input ending part way through a word is reported by finalize
+    let mut decoder = B32Decoder::new(false);
+    let mut output = Vec::new();
+    decoder.update(b"MFRGGZDFMZ", &mut output).unwrap();
+    assert!(decoder.finalize(&mut output).is_err());
+}
+
+#[test]
+fn test_slices() {
+    assert_eq!(
+        b32_encode_to_string(b"hello, world!", None).unwrap(),
+        "NBSWY3DPFQQHO33SNRSCC==="
+    );
+    assert_eq!(
+        b32_decode_to_vec(b"NBSWY3DPFQQHO33S\nNRSCC===\n", false).unwrap(),
+        b"hello, world!"
+    );
+
+    let mut output = [0; 24];
+    assert_eq!(
+        b32_encode_to_slice(b"hello, world!", &mut output, None).unwrap(),
+        24
+    );
+    assert_eq!(&output, b"NBSWY3DPFQQHO33SNRSCC===");
+    assert!(b32_encode_to_slice(b"hello, world!", &mut output[0..23], None).is_err());
+
+    let mut output = [0; 13];
+    assert_eq!(
+        b32_decode_to_slice(b"NBSWY3DPFQQHO33SNRSCC===", &mut output, false).unwrap(),
+        13
+    );
+    assert_eq!(&output, b"hello, world!");
+    assert!(b32_decode_to_slice(b"NBSWY3DPFQQHO33SNRSCC===", &mut output[0..12], false).is_err());
+}
+
+#[test]
+fn test_lengths() {
+    for n in 0..200 {
+        let data = vec![0xA5; n];
+        for wrap in [None, Some(1), Some(8), Some(13), Some(76)] {
+            let encoded = b32_encode_to_string(&data, wrap).unwrap();
+            assert_eq!(b32_encoded_len(n, wrap), Some(encoded.len()));
+            assert!(b32_decoded_len_estimate(encoded.len()) >= n);
+        }
+    }
+    assert_eq!(b32_encoded_len(5, Some(0)), None);
+}
+
+#[test]
+fn test_padding() {
+    let decode_with = |input: &[u8], padding: Padding| {
+        let mut decoder = B32Decoder::with_config(&B32Config::STANDARD.padding(padding));
+        let mut output = Vec::new();
+        decoder.update(input, &mut output)?;
+        decoder.finalize(&mut output)?;
+        Ok::<Vec<u8>, std::io::Error>(output)
+    };
+
+    // padding is required by default
+    assert!(decode_with(b"MFRA", Padding::Required).is_err());
+    assert_eq!(decode_with(b"MFRA====", Padding::Required).unwrap(), b"ab");
+
+    // either is accepted when indifferent, but only for lengths an encoder produces
+    for (data, encoded) in [
+        (&b"a"[..], &b"ME"[..]),
+        (b"ab", b"MFRA"),
+        (b"abc", b"MFRGG"),
+        (b"abcd", b"MFRGGZA"),
+        (b"abcde", b"MFRGGZDF"),
+    ] {
+        assert_eq!(decode_with(encoded, Padding::Indifferent).unwrap(), data);
+    }
+    assert!(decode_with(b"MFR", Padding::Indifferent).is_err());
+    assert!(decode_with(b"MFRGGZ", Padding::Indifferent).is_err());
+
+    // padding is an error when forbidden
+    assert_eq!(decode_with(b"MFRGG", Padding::Forbidden).unwrap(), b"abc");
+    assert!(decode_with(b"MFRGG===", Padding::Forbidden).is_err());
+    assert!(decode_with(b"MFRGGZDFMFRA====", Padding::Forbidden).is_err());
+
+    // encoders can leave off padding
+    let mut encoder =
+        B32Encoder::with_config(&B32Config::STANDARD.padding(Padding::Forbidden)).unwrap();
+    let mut output = Vec::new();
+    encoder.update(b"hello, world!", &mut output);
+    encoder.finalize(&mut output);
+    assert_eq!(output, b"NBSWY3DPFQQHO33SNRSCC");
+}
+
+#[test]
+fn test_strict() {
+    let decode_with = |input: &[u8], padding: Padding, strict: bool| {
+        let mut decoder =
+            B32Decoder::with_config(&B32Config::STANDARD.padding(padding).strict(strict));
+        let mut output = Vec::new();
+        decoder.update(input, &mut output)?;
+        decoder.finalize(&mut output)?;
+        Ok::<Vec<u8>, std::io::Error>(output)
+    };
+
+    // each final word length, canonical and with a trailing bit set
+    for (data, canonical, non_canonical) in [
+        (&b"a"[..], &b"ME======"[..], &b"MF======"[..]),
+        (b"ab", b"MFRA====", b"MFRB===="),
+        (b"abc", b"MFRGG===", b"MFRGH==="),
+        (b"abcd", b"MFRGGZA=", b"MFRGGZB="),
+    ] {
+        assert_eq!(
+            decode_with(non_canonical, Padding::Required, false).unwrap(),
+            data
+        );
+        assert_eq!(
+            decode_with(canonical, Padding::Required, true).unwrap(),
+            data
+        );
+        assert!(decode_with(non_canonical, Padding::Required, true).is_err());
+
+        let unpadded = &non_canonical[0..non_canonical.iter().position(|&c| c == b'=').unwrap()];
+        assert!(decode_with(unpadded, Padding::Indifferent, true).is_err());
+    }
+
+    // errors are at the last data char, however the input is split
+    let mut decoder = B32Decoder::with_config(&B32Config::STANDARD.strict(true));
+    let mut output = Vec::new();
+    let error = b"MFRGGZB="
+        .iter()
+        .try_for_each(|&c| decoder.update(&[c], &mut output).map(|_| ()))
+        .unwrap_err();
+    assert_eq!(
+        error
+            .get_ref()
+            .unwrap()
+            .downcast_ref::<DecodeError>()
+            .unwrap()
+            .position()
+            .column,
+        7
+    );
+}
+
+#[test]
+fn test_decode_policy() {
+    let error = b32_decode_to_vec(b"ME======\n", DecodePolicy::NoWhitespace).unwrap_err();
+    assert!(matches!(
+        error.get_ref().unwrap().downcast_ref::<DecodeError>(),
+        Some(DecodeError::InvalidByte { byte: b'\n', .. })
+    ));
+    assert_eq!(
+        b32_decode_to_vec(b"ME======MFRA====", DecodePolicy::Concatenated).unwrap(),
+        b"aab"
+    );
+    assert!(b32_decode_to_vec(b"ME======MFRA====", DecodePolicy::WhitespaceTolerant).is_err());
+
+    let mut reader = BufReader::new(&b"ME======MFRA===="[..]);
+    let mut output = Vec::new();
+    b32_decode_buf(&mut reader, &mut output, DecodePolicy::StopAtPadding).unwrap();
+    assert_eq!(output, b"a");
+    let mut rest = Vec::new();
+    reader.read_to_end(&mut rest).unwrap();
+    assert_eq!(rest, b"MFRA====");
+}
+
+#[test]
+fn test_concatenated() {
+    let mut input = Vec::new();
+    let mut expected = Vec::new();
+    for i in 0..1000 {
+        let data: Vec<u8> = (0..i % 97).map(|j| (i + j) as u8).collect();
+        b32_encode(&mut &data[..], &mut input, Some(76)).unwrap();
+        expected.extend(data);
+    }
+    let mut output = Vec::new();
+    b32_decode(&mut &input[..], &mut output, DecodePolicy::Concatenated).unwrap();
+    assert_eq!(output, expected);
+    assert!(b32_decode(&mut &input[..], &mut Vec::new(), false).is_err());
+}
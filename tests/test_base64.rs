@@ -1,5 +1,5 @@
 use base_util::*;
-use std::io::{BufReader, BufWriter};
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Write};
 
 fn encode(input: &[u8], wrap: Option<usize>) -> Result<Vec<u8>, std::io::Error> {
     let output = Vec::new();
@@ -66,6 +66,15 @@ fn test_wrapping() {
         Some(20),
         true,
     );
+
+    // empty data has no line to end, however it is encoded
+    test_encode(b"", b"", Some(20));
+    let mut output = Vec::new();
+    B64Encoder::new(Some(20)).unwrap().finalize(&mut output);
+    assert_eq!(output, b"");
+    let writer = B64EncoderWriter::new(Vec::new(), Some(20)).unwrap();
+    assert_eq!(writer.finish().unwrap(), b"");
+    assert_eq!(b64_encoded_len(0, Some(20)), Some(0));
 }
 
 #[test]
@@ -89,3 +98,538 @@ fn test_ignore_invalid_char() {
 fn test_invalid_wrap() {
     test_encode_err(b"hello world", Some(0));
 }
+
+fn decode_error(input: &[u8], ignore_garbage: bool) -> DecodeError {
+    let err = decode(input, ignore_garbage).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    *err.get_ref()
+        .unwrap()
+        .downcast_ref::<DecodeError>()
+        .unwrap()
+}
+
+fn position(offset: usize, line: usize, column: usize) -> Position {
+    Position {
+        offset,
+        line,
+        column,
+    }
+}
+
+#[test]
+fn test_error_positions() {
+    assert_eq!(
+        decode_error(b"VGhlIHF1\naWNr^IGJyb3du\n", false),
+        DecodeError::InvalidByte {
+            byte: b'^',
+            position: position(13, 2, 5)
+        }
+    );
+    assert_eq!(
+        decode_error(b"VGhlIHF1\nY===\n", false),
+        DecodeError::InvalidPadding {
+            position: position(12, 2, 4)
+        }
+    );
+    assert_eq!(
+        decode_error(b"YQ==\n  Yg==\n", false),
+        DecodeError::DataAfterPadding {
+            position: position(7, 2, 3)
+        }
+    );
+    assert_eq!(
+        decode_error(b"YWJj\nYW\n", false),
+        DecodeError::TruncatedInput {
+            position: position(8, 3, 1)
+        }
+    );
+}
+
+#[test]
+fn test_error_position_across_buffers() {
+    let mut input = Vec::new();
+    b64_encode(&mut &[0; 100000][..], &mut input, Some(76)).unwrap();
+    input[70000] = b'!';
+    assert_eq!(
+        decode_error(&input, false),
+        DecodeError::InvalidByte {
+            byte: b'!',
+            position: position(70000, 70000 / 77 + 1, 70000 % 77 + 1)
+        }
+    );
+
+    // an invalid byte completing a word that started in the previous buffer
+    let mut input = b"\n".to_vec();
+    b64_encode(&mut &[0; 60000][..], &mut input, None).unwrap();
+    input[65536] = b'!';
+    assert_eq!(
+        decode_error(&input, false),
+        DecodeError::InvalidByte {
+            byte: b'!',
+            position: position(65536, 2, 65536)
+        }
+    );
+}
+
+#[test]
+fn test_first_error_whatever_the_buffer_size() {
+    // the data after padding comes before the invalid byte, however the word is split
+    let config = B64Config::STANDARD.decode_policy(DecodePolicy::NoWhitespace);
+    let input = b"ysc+uhQ=D!Ge";
+    let expected = DecodeError::DataAfterPadding {
+        position: position(8, 1, 9),
+    };
+    let error = config.decode_to_vec(input).unwrap_err();
+    assert_eq!(error.get_ref().unwrap().downcast_ref(), Some(&expected));
+    for buffer_size in 1..=input.len() {
+        let error = config
+            .buffer_size(buffer_size)
+            .decode(&mut &input[..], &mut Vec::new())
+            .unwrap_err();
+        assert_eq!(error.get_ref().unwrap().downcast_ref(), Some(&expected));
+    }
+}
+
+#[test]
+fn test_decoder_reader() {
+    let mut output = Vec::new();
+    B64DecoderReader::new(&b"SGVsbG8s\nIHdvcmxkIQ==\n"[..], false)
+        .read_to_end(&mut output)
+        .unwrap();
+    assert_eq!(output, b"Hello, world!");
+
+    // reads smaller than the decoded data return it piece by piece
+    let mut reader = B64DecoderReader::new(&b"YWJjZGVm"[..], false);
+    let mut buf = [0; 4];
+    assert_eq!(reader.read(&mut buf).unwrap(), 4);
+    assert_eq!(&buf, b"abcd");
+    assert_eq!(reader.read(&mut buf).unwrap(), 2);
+    assert_eq!(&buf[0..2], b"ef");
+    assert_eq!(reader.read(&mut buf).unwrap(), 0);
+
+    // anything after the final padded word is skipped when ignoring garbage
+    let mut output = Vec::new();
+    B64DecoderReader::new(&b"YQ==Yg==!"[..], true)
+        .read_to_end(&mut output)
+        .unwrap();
+    assert_eq!(output, b"a");
+}
+
+#[test]
+fn test_decoder_reader_large() {
+    let data: Vec<u8> = (0..200000).map(|i| (i * 7 % 251) as u8).collect();
+    let mut input = Vec::new();
+    b64_encode(&mut &data[..], &mut input, Some(76)).unwrap();
+
+    let mut output = Vec::new();
+    B64DecoderReader::new(&input[..], false)
+        .read_to_end(&mut output)
+        .unwrap();
+    assert_eq!(output, data);
+}
+
+#[test]
+fn test_decoder_reader_errors() {
+    let mut output = Vec::new();
+    let error = B64DecoderReader::new(&b"YWJj\nY!Jj"[..], false)
+        .read_to_end(&mut output)
+        .unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+    assert_eq!(
+        error.get_ref().unwrap().downcast_ref::<DecodeError>(),
+        Some(&DecodeError::InvalidByte {
+            byte: b'!',
+            position: position(6, 2, 2)
+        })
+    );
+
+    let error = B64DecoderReader::new(&b"YWJjYW"[..], false)
+        .read_to_end(&mut output)
+        .unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_encoder_writer() {
+    // leftover bytes are carried between writes
+    let mut writer = B64EncoderWriter::new(Vec::new(), None).unwrap();
+    writer.write_all(b"Hel").unwrap();
+    writer.write_all(b"lo, w").unwrap();
+    writer.write_all(b"orld!").unwrap();
+    assert_eq!(writer.finish().unwrap(), b"SGVsbG8sIHdvcmxkIQ==");
+
+    // the wrap column is carried between writes
+    let mut writer = B64EncoderWriter::new(Vec::new(), Some(8)).unwrap();
+    for &b in b"Hello, world!" {
+        writer.write_all(&[b]).unwrap();
+    }
+    assert_eq!(writer.finish().unwrap(), b"SGVsbG8s\nIHdvcmxk\nIQ==\n");
+
+    // dropping the writer finishes it
+    let mut output = Vec::new();
+    {
+        let mut writer = B64EncoderWriter::new(&mut output, None).unwrap();
+        writer.write_all(b"ab").unwrap();
+    }
+    assert_eq!(output, b"YWI=");
+
+    assert!(B64EncoderWriter::new(Vec::new(), Some(0)).is_err());
+}
+
+#[test]
+fn test_encoder_writer_large() {
+    let data: Vec<u8> = (0..200000).map(|i| (i * 7 % 251) as u8).collect();
+    let mut expected = Vec::new();
+    b64_encode(&mut &data[..], &mut expected, Some(76)).unwrap();
+
+    let mut writer = B64EncoderWriter::new(Vec::new(), Some(76)).unwrap();
+    for chunk in data.chunks(1000) {
+        writer.write_all(chunk).unwrap();
+    }
+    assert_eq!(writer.finish().unwrap(), expected);
+}
+
+#[test]
+fn test_incremental_encoder() {
+    let mut encoder = B64Encoder::new(Some(8)).unwrap();
+    let mut output = Vec::new();
+    for fragment in [&b"H"[..], b"ello", b"", b", wor", b"ld!"] {
+        encoder.update(fragment, &mut output);
+    }
+    encoder.finalize(&mut output);
+    assert_eq!(output, b"SGVsbG8s\nIHdvcmxk\nIQ==\n");
+
+    assert!(B64Encoder::new(Some(0)).is_err());
+}
+
+#[test]
+fn test_incremental_decoder() {
+    let mut decoder = B64Decoder::new(false);
+    let mut output = Vec::new();
+    for fragment in [&b"S"[..], b"GVsbG8s\nI", b"", b"HdvcmxkIQ", b"==\n"] {
+        decoder.update(fragment, &mut output).unwrap();
+    }
+    decoder.finalize(&mut output).unwrap();
+    assert_eq!(output, b"Hello, world!");
+
+    // errors report positions across fragments, and leave earlier output intact
+    let mut decoder = B64Decoder::new(false);
+    let mut output = Vec::new();
+    decoder.update(b"YWJj\nYW", &mut output).unwrap();
+    let error = decoder.update(b"!j", &mut output).unwrap_err();
+    assert_eq!(
+        error.get_ref().unwrap().downcast_ref::<DecodeError>(),
+        Some(&DecodeError::InvalidByte {
+            byte: b'!',
+            position: position(7, 2, 3)
+        })
+    );
+    assert_eq!(output, b"abc");
+
+    // when ignoring garbage, anything after the final padded word is skipped
+    let mut decoder = B64Decoder::new(true);
+    let mut output = Vec::new();
+    decoder.update(b"YWJjYQ", &mut output).unwrap();
+    decoder.update(b"==Yg==!", &mut output).unwrap();
+    decoder.finalize(&mut output).unwrap();
+    assert_eq!(output, b"abca");
+
+    // input ending part way through a word is reported by finalize
+    let mut decoder = B64Decoder::new(false);
+    let mut output = Vec::new();
+    decoder.update(b"YWJjYW", &mut output).unwrap();
+    assert_eq!(output, b"abc");
+    assert!(decoder.finalize(&mut output).is_err());
+}
+
+#[test]
+fn test_slices() {
+    assert_eq!(
+        b64_encode_to_string(b"Hello, world!", None).unwrap(),
+        "SGVsbG8sIHdvcmxkIQ=="
+    );
+    assert_eq!(
+        b64_decode_to_vec(b"SGVsbG8s\nIHdvcmxkIQ==\n", false).unwrap(),
+        b"Hello, world!"
+    );
+
+    let mut output = [0; 20];
+    assert_eq!(
+        b64_encode_to_slice(b"Hello, world!", &mut output, None).unwrap(),
+        20
+    );
+    assert_eq!(&output, b"SGVsbG8sIHdvcmxkIQ==");
+    assert!(b64_encode_to_slice(b"Hello, world!", &mut output[0..19], None).is_err());
+
+    let mut output = [0; 13];
+    assert_eq!(
+        b64_decode_to_slice(b"SGVsbG8sIHdvcmxkIQ==", &mut output, false).unwrap(),
+        13
+    );
+    assert_eq!(&output, b"Hello, world!");
+    assert!(b64_decode_to_slice(b"SGVsbG8sIHdvcmxkIQ==", &mut output[0..12], false).is_err());
+    assert!(b64_decode_to_slice(b"SGVsbG8sIHdvcmxkIQ", &mut output, false).is_err());
+}
+
+#[test]
+fn test_lengths() {
+    for n in 0..200 {
+        let data = vec![0xA5; n];
+        for wrap in [None, Some(1), Some(4), Some(7), Some(76)] {
+            let encoded = b64_encode_to_string(&data, wrap).unwrap();
+            assert_eq!(b64_encoded_len(n, wrap), Some(encoded.len()));
+            assert!(b64_decoded_len_estimate(encoded.len()) >= n);
+        }
+    }
+    assert_eq!(b64_encoded_len(3, Some(0)), None);
+    assert_eq!(b64_encoded_len(usize::MAX, None), None);
+}
+
+#[test]
+fn test_padding() {
+    let decode_with = |input: &[u8], padding: Padding| {
+        let mut decoder = B64Decoder::with_config(&B64Config::STANDARD.padding(padding));
+        let mut output = Vec::new();
+        decoder.update(input, &mut output)?;
+        decoder.finalize(&mut output)?;
+        Ok::<Vec<u8>, std::io::Error>(output)
+    };
+
+    // padding is required by default
+    assert!(decode_with(b"YQ", Padding::Required).is_err());
+    assert_eq!(decode_with(b"YQ==", Padding::Required).unwrap(), b"a");
+
+    // either is accepted when indifferent
+    assert_eq!(decode_with(b"YQ", Padding::Indifferent).unwrap(), b"a");
+    assert_eq!(decode_with(b"YWI\n", Padding::Indifferent).unwrap(), b"ab");
+    assert_eq!(decode_with(b"YWI=", Padding::Indifferent).unwrap(), b"ab");
+    assert!(decode_with(b"YWJjY", Padding::Indifferent).is_err());
+    assert!(decode_with(b"YQ=", Padding::Indifferent).is_err());
+
+    // padding is reported where it appears when forbidden
+    assert_eq!(decode_with(b"YWJj", Padding::Forbidden).unwrap(), b"abc");
+    assert_eq!(decode_with(b"YWI", Padding::Forbidden).unwrap(), b"ab");
+    let error = decode_with(b"YWJj\nYQ==", Padding::Forbidden).unwrap_err();
+    assert_eq!(
+        error.get_ref().unwrap().downcast_ref::<DecodeError>(),
+        Some(&DecodeError::InvalidPadding {
+            position: position(7, 2, 3)
+        })
+    );
+    let error = decode_with(b"YWI=", Padding::Forbidden).unwrap_err();
+    assert_eq!(
+        error.get_ref().unwrap().downcast_ref::<DecodeError>(),
+        Some(&DecodeError::InvalidPadding {
+            position: position(3, 1, 4)
+        })
+    );
+
+    // readers decode an unpadded final word at the end of input
+    let mut output = Vec::new();
+    B64DecoderReader::with_config(
+        &b"SGVsbG8sIHdvcmxkIQ"[..],
+        &B64Config::STANDARD.padding(Padding::Indifferent),
+    )
+    .read_to_end(&mut output)
+    .unwrap();
+    assert_eq!(output, b"Hello, world!");
+
+    // encoders can leave off padding
+    let mut encoder =
+        B64Encoder::with_config(&B64Config::STANDARD.padding(Padding::Forbidden)).unwrap();
+    let mut output = Vec::new();
+    encoder.update(b"Hello, world!", &mut output);
+    encoder.finalize(&mut output);
+    assert_eq!(output, b"SGVsbG8sIHdvcmxkIQ");
+
+    let mut writer = B64EncoderWriter::with_config(
+        Vec::new(),
+        &B64Config::STANDARD
+            .wrap(Some(4))
+            .padding(Padding::Forbidden),
+    )
+    .unwrap();
+    writer.write_all(b"abcde").unwrap();
+    assert_eq!(writer.finish().unwrap(), b"YWJj\nZGU\n");
+}
+
+#[test]
+fn test_strict() {
+    let decode_with = |input: &[u8], padding: Padding, strict: bool| {
+        let mut decoder =
+            B64Decoder::with_config(&B64Config::STANDARD.padding(padding).strict(strict));
+        let mut output = Vec::new();
+        decoder.update(input, &mut output)?;
+        decoder.finalize(&mut output)?;
+        Ok::<Vec<u8>, std::io::Error>(output)
+    };
+
+    // trailing bits are discarded by default
+    assert_eq!(
+        decode_with(b"QQ==", Padding::Required, false).unwrap(),
+        b"A"
+    );
+    assert_eq!(
+        decode_with(b"QR==", Padding::Required, false).unwrap(),
+        b"A"
+    );
+    assert_eq!(
+        decode_with(b"QUI=", Padding::Required, false).unwrap(),
+        b"AB"
+    );
+    assert_eq!(
+        decode_with(b"QUJ=", Padding::Required, false).unwrap(),
+        b"AB"
+    );
+
+    // only the canonical encoding is accepted in strict mode
+    assert_eq!(decode_with(b"QQ==", Padding::Required, true).unwrap(), b"A");
+    assert_eq!(
+        decode_with(b"QUI=", Padding::Required, true).unwrap(),
+        b"AB"
+    );
+    let error = decode_with(b"QUJD\nQR==", Padding::Required, true).unwrap_err();
+    assert_eq!(
+        error.get_ref().unwrap().downcast_ref::<DecodeError>(),
+        Some(&DecodeError::NonCanonicalTrailingBits {
+            position: position(6, 2, 2)
+        })
+    );
+    assert!(decode_with(b"QUJ=", Padding::Required, true).is_err());
+
+    // the position is the same however the input is split
+    let mut decoder = B64Decoder::with_config(&B64Config::STANDARD.strict(true));
+    let mut output = Vec::new();
+    let error = b"QUJD\nQR=="
+        .iter()
+        .try_for_each(|&c| decoder.update(&[c], &mut output).map(|_| ()))
+        .unwrap_err();
+    assert_eq!(
+        error.get_ref().unwrap().downcast_ref::<DecodeError>(),
+        Some(&DecodeError::NonCanonicalTrailingBits {
+            position: position(6, 2, 2)
+        })
+    );
+
+    // including for an unpadded final word
+    assert_eq!(
+        decode_with(b"QQ", Padding::Indifferent, true).unwrap(),
+        b"A"
+    );
+    let error = decode_with(b"QR\n", Padding::Indifferent, true).unwrap_err();
+    assert_eq!(
+        error.get_ref().unwrap().downcast_ref::<DecodeError>(),
+        Some(&DecodeError::NonCanonicalTrailingBits {
+            position: position(1, 1, 2)
+        })
+    );
+
+    let mut output = Vec::new();
+    assert!(
+        B64DecoderReader::with_config(&b"QR=="[..], &B64Config::STANDARD.strict(true))
+            .read_to_end(&mut output)
+            .is_err()
+    );
+}
+
+#[test]
+fn test_decode_policy() {
+    // NoWhitespace rejects whitespace
+    assert_eq!(
+        b64_decode_to_vec(b"YWJj", DecodePolicy::NoWhitespace).unwrap(),
+        b"abc"
+    );
+    let error = b64_decode_to_vec(b"YWJj\n", DecodePolicy::NoWhitespace).unwrap_err();
+    assert_eq!(
+        error.get_ref().unwrap().downcast_ref::<DecodeError>(),
+        Some(&DecodeError::InvalidByte {
+            byte: b'\n',
+            position: position(4, 1, 5)
+        })
+    );
+
+    // including after the final padded word
+    let error = b64_decode_to_vec(b"QQ==\n", DecodePolicy::NoWhitespace).unwrap_err();
+    assert_eq!(
+        error.get_ref().unwrap().downcast_ref::<DecodeError>(),
+        Some(&DecodeError::InvalidByte {
+            byte: b'\n',
+            position: position(4, 1, 5)
+        })
+    );
+
+    // bools select the original behaviors
+    assert_eq!(DecodePolicy::from(false), DecodePolicy::WhitespaceTolerant);
+    assert_eq!(DecodePolicy::from(true), DecodePolicy::IgnoreGarbage);
+    assert_eq!(
+        b64_decode_to_vec(b"YW!Jj\nYQ==Yg==", DecodePolicy::IgnoreGarbage).unwrap(),
+        b"abca"
+    );
+    assert!(b64_decode_to_vec(b"YQ==Yg==", DecodePolicy::WhitespaceTolerant).is_err());
+
+    // concatenated segments are decoded in turn
+    assert_eq!(
+        b64_decode_to_vec(b"YQ==Yg==\nYWJj", DecodePolicy::Concatenated).unwrap(),
+        b"ababc"
+    );
+}
+
+#[test]
+fn test_stop_at_padding() {
+    // the rest of the input is left in the reader
+    let mut reader = BufReader::new(&b"YQ==\nYg=="[..]);
+    let mut output = Vec::new();
+    b64_decode_buf(&mut reader, &mut output, DecodePolicy::StopAtPadding).unwrap();
+    assert_eq!(output, b"a");
+    let mut rest = Vec::new();
+    reader.read_to_end(&mut rest).unwrap();
+    assert_eq!(rest, b"\nYg==");
+
+    // and reported as unconsumed by the incremental decoder
+    let mut decoder = B64Decoder::new(DecodePolicy::StopAtPadding);
+    let mut output = Vec::new();
+    assert_eq!(decoder.update(b"YWJj", &mut output).unwrap(), 4);
+    assert_eq!(decoder.update(b"YQ==Yg==", &mut output).unwrap(), 4);
+    assert!(decoder.is_finished());
+    assert_eq!(decoder.update(b"Yg==", &mut output).unwrap(), 0);
+    decoder.finalize(&mut output).unwrap();
+    assert_eq!(output, b"abca");
+}
+
+#[test]
+fn test_concatenated() {
+    // segments may be separated by whitespace, and end in a full word
+    for (input, expected) in [
+        (&b"YQ==Yg=="[..], &b"ab"[..]),
+        (b"YQ==\nYg==\n", b"ab"),
+        (b"YWJj\nYQ==\nYWI=YWJj\n", b"abcaababc"),
+        (b"YQ==", b"a"),
+    ] {
+        assert_eq!(
+            b64_decode_to_vec(input, DecodePolicy::Concatenated).unwrap(),
+            expected
+        );
+    }
+
+    // each segment must still be well formed
+    assert!(b64_decode_to_vec(b"YQ==Y===", DecodePolicy::Concatenated).is_err());
+    assert!(b64_decode_to_vec(b"YQ==Yg", DecodePolicy::Concatenated).is_err());
+
+    // segment boundaries across buffers
+    let mut input = Vec::new();
+    let mut expected = Vec::new();
+    for i in 0..1000 {
+        let data: Vec<u8> = (0..i % 97).map(|j| (i + j) as u8).collect();
+        b64_encode(&mut &data[..], &mut input, Some(76)).unwrap();
+        expected.extend(data);
+    }
+    let mut output = Vec::new();
+    b64_decode(&mut &input[..], &mut output, DecodePolicy::Concatenated).unwrap();
+    assert_eq!(output, expected);
+
+    let mut output = Vec::new();
+    B64DecoderReader::new(&input[..], DecodePolicy::Concatenated)
+        .read_to_end(&mut output)
+        .unwrap();
+    assert_eq!(output, expected);
+}
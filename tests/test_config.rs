@@ -0,0 +1,231 @@
+use base_util::*;
+use std::io::{BufReader, ErrorKind, Read, Write};
+
+// a profile declared once and reused
+const WRAPPED: B64Config = B64Config::STANDARD
+    .wrap(Some(8))
+    .line_ending(LineEnding::CrLf)
+    .buffer_size(7);
+
+fn encode(config: &B64Config, input: &[u8]) -> Vec<u8> {
+    let mut output = Vec::new();
+    config.encode(&mut &input[..], &mut output).unwrap();
+    output
+}
+
+#[test]
+fn test_standard_profile() {
+    let data: Vec<u8> = (0..1000).map(|i| (i * 13 % 256) as u8).collect();
+    for wrap in [None, Some(1), Some(76)] {
+        let config = B64Config::STANDARD.wrap(wrap);
+        let encoded = b64_encode_to_string(&data, wrap).unwrap();
+        assert_eq!(config.encode_to_string(&data).unwrap(), encoded);
+        assert_eq!(encode(&config, &data), encoded.as_bytes());
+        assert_eq!(config.decode_to_vec(encoded.as_bytes()).unwrap(), data);
+    }
+
+    let config = B32Config::STANDARD.wrap(Some(76));
+    let encoded = b32_encode_to_string(&data, Some(76)).unwrap();
+    assert_eq!(config.encode_to_string(&data).unwrap(), encoded);
+    assert_eq!(config.decode_to_vec(encoded.as_bytes()).unwrap(), data);
+
+    // wrapping on column 0 is an error when encoding
+    let config = B64Config::STANDARD.wrap(Some(0));
+    assert!(config.encode_to_string(b"foo").is_err());
+    assert!(B64Encoder::with_config(&config).is_err());
+}
+
+#[test]
+fn test_line_endings() {
+    assert_eq!(LineEnding::Lf.as_bytes(), b"\n");
+    assert_eq!(LineEnding::CrLf.as_bytes(), b"\r\n");
+
+    assert_eq!(
+        encode(&WRAPPED, b"foobarbazqux"),
+        b"Zm9vYmFy\r\nYmF6cXV4\r\n"
+    );
+    assert_eq!(encode(&WRAPPED, b"foobarbaz"), b"Zm9vYmFy\r\nYmF6\r\n");
+    // no line is ended for empty data
+    assert_eq!(encode(&WRAPPED, b""), b"");
+    assert_eq!(
+        B32Config::STANDARD
+            .wrap(Some(10))
+            .line_ending(LineEnding::CrLf)
+            .encode_to_string(b"foobar")
+            .unwrap(),
+        "MZXW6YTBOI\r\n======\r\n"
+    );
+
+    // CRLF is whitespace when decoding
+    assert_eq!(
+        WRAPPED.decode_to_vec(b"Zm9vYmFy\r\nYmF6\r\n").unwrap(),
+        b"foobarbaz"
+    );
+
+    // line endings are kept across calls to an encoder
+    let mut output = Vec::new();
+    let mut encoder = B64Encoder::with_config(&WRAPPED).unwrap();
+    for b in b"foobarbazqux" {
+        encoder.update(&[*b], &mut output);
+    }
+    encoder.finalize(&mut output);
+    assert_eq!(output, b"Zm9vYmFy\r\nYmF6cXV4\r\n");
+}
+
+#[test]
+fn test_url_safe_profile() {
+    let config = B64Config::URL_SAFE;
+    assert_eq!(config.encode_to_string(b"\xFB\xFF").unwrap(), "-_8");
+    assert_eq!(config.decode_to_vec(b"-_8").unwrap(), b"\xFB\xFF");
+    assert_eq!(config.decode_to_vec(b"-_8=").unwrap(), b"\xFB\xFF");
+
+    // padding can be required again
+    let config = B64Config::URL_SAFE.padding(Padding::Required);
+    assert_eq!(config.encode_to_string(b"\xFB\xFF").unwrap(), "-_8=");
+    assert!(config.decode_to_vec(b"-_8").is_err());
+
+    let config = B32Config::EXTENDED_HEX.padding(Padding::Forbidden);
+    assert_eq!(config.encode_to_string(b"foobar").unwrap(), "CPNMUOJ1E8");
+    assert_eq!(config.decode_to_vec(b"CPNMUOJ1E8").unwrap(), b"foobar");
+    assert!(config.decode_to_vec(b"CPNMUOJ1E8======").is_err());
+}
+
+#[test]
+fn test_decode_settings() {
+    let config = B64Config::STANDARD.decode_policy(DecodePolicy::NoWhitespace);
+    assert!(config.decode_to_vec(b"Zm9v\n").is_err());
+    assert_eq!(config.decode_to_vec(b"Zm9v").unwrap(), b"foo");
+
+    let config = B64Config::STANDARD.decode_policy(DecodePolicy::IgnoreGarbage);
+    assert_eq!(config.decode_to_vec(b"Zm!9v").unwrap(), b"foo");
+
+    let config = B64Config::STANDARD.strict(true);
+    assert!(config.decode_to_vec(b"QR==").is_err());
+    assert_eq!(config.decode_to_vec(b"QQ==").unwrap(), b"A");
+
+    // stopping at padding leaves the rest of a buffered reader
+    let config = B32Config::STANDARD.decode_policy(DecodePolicy::StopAtPadding);
+    let mut reader = BufReader::new(&b"MY======rest"[..]);
+    let mut output = Vec::new();
+    config.decode_buf(&mut reader, &mut output).unwrap();
+    assert_eq!(output, b"f");
+    let mut rest = Vec::new();
+    reader.read_to_end(&mut rest).unwrap();
+    assert_eq!(rest, b"rest");
+
+    let error = B64Config::STANDARD
+        .decode(&mut &b"Zm9v!"[..], &mut Vec::new())
+        .unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_buffer_size() {
+    let data: Vec<u8> = (0..10000).map(|i| (i * 7 % 256) as u8).collect();
+    let expected = b64_encode_to_string(&data, Some(76)).unwrap();
+
+    for buffer_size in [1, 2, 3, 4, 5, 100, 65536] {
+        let config = B64Config::STANDARD.wrap(Some(76)).buffer_size(buffer_size);
+        assert_eq!(encode(&config, &data), expected.as_bytes());
+
+        let mut output = Vec::new();
+        config
+            .decode(&mut expected.as_bytes(), &mut output)
+            .unwrap();
+        assert_eq!(output, data);
+
+        let mut writer = B64EncoderWriter::with_config(Vec::new(), &config).unwrap();
+        writer.write_all(&data).unwrap();
+        assert_eq!(writer.finish().unwrap(), expected.as_bytes());
+
+        let mut reader = B64DecoderReader::with_config(expected.as_bytes(), &config);
+        let mut output = Vec::new();
+        reader.read_to_end(&mut output).unwrap();
+        assert_eq!(output, data);
+
+        let config = B32Config::STANDARD.buffer_size(buffer_size);
+        let encoded = config.encode_to_string(&data).unwrap();
+        let mut reader = B32DecoderReader::with_config(encoded.as_bytes(), &config);
+        let mut output = Vec::new();
+        reader.read_to_end(&mut output).unwrap();
+        assert_eq!(output, data);
+    }
+}
+
+#[test]
+#[should_panic(expected = "buffer size cannot be 0")]
+fn test_buffer_size_zero() {
+    let _ = B64Config::STANDARD.buffer_size(0);
+}
+
+#[test]
+fn test_encoded_len() {
+    let data = [0u8; 100];
+    for n in 0..data.len() {
+        for wrap in [None, Some(1), Some(4), Some(7), Some(76)] {
+            for line_ending in [LineEnding::Lf, LineEnding::CrLf] {
+                for padding in [Padding::Required, Padding::Indifferent, Padding::Forbidden] {
+                    let config = B64Config::STANDARD
+                        .wrap(wrap)
+                        .line_ending(line_ending)
+                        .padding(padding);
+                    let encoded = config.encode_to_string(&data[0..n]).unwrap();
+                    assert_eq!(config.encoded_len(n), Some(encoded.len()));
+
+                    let config = B32Config::STANDARD
+                        .wrap(wrap)
+                        .line_ending(line_ending)
+                        .padding(padding);
+                    let encoded = config.encode_to_string(&data[0..n]).unwrap();
+                    assert_eq!(config.encoded_len(n), Some(encoded.len()));
+                }
+            }
+        }
+    }
+    assert_eq!(B64Config::STANDARD.wrap(Some(0)).encoded_len(1), None);
+    assert_eq!(B64Config::URL_SAFE.encoded_len(usize::MAX), None);
+}
+
+#[test]
+fn test_pem_profile() {
+    let data = [0u8; 100];
+    let encoded = B64Config::PEM.encode_to_string(&data).unwrap();
+
+    // lines of 64 chars, each ending with LF
+    let lines: Vec<&str> = encoded.split_terminator('\n').collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0..2].iter().all(|line| line.len() == 64));
+    assert!(encoded.ends_with("AA==\n"));
+    assert_eq!(B64Config::PEM.encoded_len(data.len()), Some(encoded.len()));
+    assert_eq!(
+        B64Config::PEM.decode_to_vec(encoded.as_bytes()).unwrap(),
+        data
+    );
+}
+
+#[test]
+fn test_mime_profile() {
+    let data: Vec<u8> = (0..1000).map(|i| (i * 31 % 256) as u8).collect();
+    let encoded = B64Config::MIME.encode_to_string(&data).unwrap();
+
+    // lines of 76 chars, each ending with CRLF
+    let lines: Vec<&str> = encoded.split_terminator("\r\n").collect();
+    assert_eq!(lines.len(), 18);
+    assert!(lines[0..17].iter().all(|line| line.len() == 76));
+    assert_eq!(lines[17].len(), 44);
+    assert!(encoded.ends_with("==\r\n"));
+    assert!(!encoded.replace("\r\n", "").contains('\n'));
+    assert_eq!(B64Config::MIME.encoded_len(data.len()), Some(encoded.len()));
+
+    // decoding is bit-exact, and ignores anything outside the alphabet
+    assert_eq!(
+        B64Config::MIME.decode_to_vec(encoded.as_bytes()).unwrap(),
+        data
+    );
+    assert_eq!(
+        B64Config::MIME
+            .decode_to_vec(b"aGVs\xFFbG8g\r\n d29y*bGQ=\r\n--boundary--\r\n")
+            .unwrap(),
+        b"hello world"
+    );
+}
@@ -0,0 +1,124 @@
+use base_util::*;
+
+fn encode(input: &[u8], media_type: Option<&str>) -> Result<String, std::io::Error> {
+    let mut output = Vec::new();
+    data_uri_encode(&mut &input[..], &mut output, media_type)?;
+    Ok(String::from_utf8(output).unwrap())
+}
+
+fn parse(uri: &str) -> DataUri {
+    data_uri_parse(uri.as_bytes()).unwrap()
+}
+
+#[test]
+fn test_sniff() {
+    assert_eq!(
+        sniff_media_type(b"\x89PNG\r\n\x1A\n\0\0"),
+        Some("image/png")
+    );
+    assert_eq!(sniff_media_type(b"\xFF\xD8\xFF\xE0"), Some("image/jpeg"));
+    assert_eq!(sniff_media_type(b"GIF89a\x01\0"), Some("image/gif"));
+    assert_eq!(sniff_media_type(b"%PDF-1.7\n"), Some("application/pdf"));
+    assert_eq!(
+        sniff_media_type(b"RIFF\x24\0\0\0WEBPVP8 "),
+        Some("image/webp")
+    );
+    assert_eq!(
+        sniff_media_type(b"<?xml version=\"1.0\"?>\n<svg xmlns=\"http://www.w3.org/2000/svg\"/>"),
+        Some("image/svg+xml")
+    );
+    assert_eq!(sniff_media_type(b"<svg></svg>\n"), Some("image/svg+xml"));
+    assert_eq!(
+        sniff_media_type(b" {\"a\": [1, 2]}\n"),
+        Some("application/json")
+    );
+    assert_eq!(sniff_media_type(b"[1, 2]"), Some("application/json"));
+    assert_eq!(sniff_media_type(b"<?xml version=\"1.0\"?><html/>"), None);
+    assert_eq!(sniff_media_type(b"RIFF\x24\0\0\0WAVE"), None);
+    assert_eq!(sniff_media_type(b""), None);
+}
+
+#[test]
+fn test_encode() {
+    assert_eq!(
+        encode(b"GIF89a", None).unwrap(),
+        "data:image/gif;base64,R0lGODlh"
+    );
+    assert_eq!(
+        encode(b"hello", None).unwrap(),
+        "data:application/octet-stream;base64,aGVsbG8="
+    );
+    assert_eq!(
+        encode(b"hello", Some("text/plain;charset=UTF-8")).unwrap(),
+        "data:text/plain;charset=UTF-8;base64,aGVsbG8="
+    );
+    assert!(encode(b"hello", Some("text/plain, evil")).is_err());
+
+    // long data is not wrapped
+    assert!(!encode(&[0u8; 1000], None).unwrap().contains('\n'));
+
+    // data split between reads is encoded whole, whether or not the media type is given
+    for media_type in [None, Some("text/plain")] {
+        let mut output = Vec::new();
+        let mut reader = std::io::Read::chain(&b"[1, "[..], &b"2]"[..]);
+        data_uri_encode(&mut reader, &mut output, media_type).unwrap();
+        let expected = format!(
+            "data:{};base64,WzEsIDJd",
+            media_type.unwrap_or("application/json")
+        );
+        assert_eq!(output, expected.as_bytes());
+    }
+}
+
+#[test]
+fn test_parse() {
+    assert_eq!(
+        parse("data:image/gif;base64,R0lGODlh"),
+        DataUri {
+            media_type: "image/gif".to_string(),
+            base64: true,
+            data: b"GIF89a".to_vec(),
+        }
+    );
+    assert_eq!(
+        parse("DATA:,A%20brief%20note\n"),
+        DataUri {
+            media_type: "text/plain;charset=US-ASCII".to_string(),
+            base64: false,
+            data: b"A brief note".to_vec(),
+        }
+    );
+    assert_eq!(
+        parse("data:;charset=UTF-8,%E2%9C%93").media_type,
+        "text/plain;charset=UTF-8"
+    );
+    assert_eq!(parse("data:;BASE64,aGk%3D").data, b"hi");
+    assert_eq!(parse("data:,%e2%9c%93").data, "\u{2713}".as_bytes());
+    assert_eq!(parse("data:text/html,<b>a,b</b>").data, b"<b>a,b</b>");
+}
+
+#[test]
+fn test_roundtrip() {
+    let data: Vec<u8> = (0..=255).collect();
+    let uri = encode(&data, None).unwrap();
+    assert_eq!(parse(&uri).data, data);
+}
+
+#[test]
+fn test_err_on_invalid_uri() {
+    assert!(data_uri_parse(b"http://example.com/").is_err());
+    assert!(data_uri_parse(b"data:text/plain").is_err());
+    assert!(data_uri_parse(b"data:,%4").is_err());
+    assert!(data_uri_parse(b"data:,%+1").is_err());
+    assert!(data_uri_parse(b"data:,%-1").is_err());
+
+    // base-64 errors are located within the URI
+    let error = data_uri_parse(b" data:;base64,aG!=").unwrap_err();
+    let position = error
+        .get_ref()
+        .unwrap()
+        .downcast_ref::<DecodeError>()
+        .unwrap()
+        .position();
+    assert_eq!((position.offset, position.column), (16, 17));
+}
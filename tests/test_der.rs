@@ -0,0 +1,92 @@
+use base_util::*;
+
+fn dump(data: &[u8]) -> Result<String, std::io::Error> {
+    let mut output = Vec::new();
+    der_dump(data, &mut output)?;
+    Ok(String::from_utf8(output).unwrap())
+}
+
+#[test]
+fn test_dump() {
+    let data = b"\x30\x1A\
+        \x02\x02\xFF\x7F\
+        \x06\x03\x55\x04\x03\
+        \x13\x02hi\
+        \x03\x05\x00\x30\x02\x05\x00\
+        \x01\x01\xFF\
+        \x80\x01\xAB";
+    assert_eq!(
+        dump(data).unwrap(),
+        "     0: SEQUENCE, 26 bytes\n\
+         \x20    2:   INTEGER, 2 bytes: -129\n\
+         \x20    6:   OBJECT IDENTIFIER, 3 bytes: 2.5.4.3 (commonName)\n\
+         \x20   11:   PrintableString, 2 bytes: \"hi\"\n\
+         \x20   15:   BIT STRING, 5 bytes\n\
+         \x20   18:     SEQUENCE, 2 bytes\n\
+         \x20   20:       NULL, 0 bytes\n\
+         \x20   22:   BOOLEAN, 1 byte: true\n\
+         \x20   25:   [0], 1 byte: AB\n"
+    );
+    assert_eq!(dump(b"").unwrap(), "");
+}
+
+#[test]
+fn test_values() {
+    // long-form lengths
+    let mut octets = b"\x04\x81\xC8".to_vec();
+    octets.extend_from_slice(&[0x11; 200]);
+    assert!(dump(&octets)
+        .unwrap()
+        .starts_with("     0: OCTET STRING, 200 bytes: 111111"));
+
+    // integers too large to print in decimal
+    let mut integer = b"\x02\x11\x01".to_vec();
+    integer.extend_from_slice(&[0; 16]);
+    assert_eq!(
+        dump(&integer).unwrap(),
+        "     0: INTEGER, 17 bytes: 0100000000000000000000000000000000\n"
+    );
+
+    assert_eq!(
+        dump(b"\x06\x03\x88\x37\x03").unwrap(),
+        "     0: OBJECT IDENTIFIER, 3 bytes: 2.999.3\n"
+    );
+    assert_eq!(
+        dump(b"\x1E\x04\x00h\x00i").unwrap(),
+        "     0: BMPString, 4 bytes: \"hi\"\n"
+    );
+    assert_eq!(
+        dump(b"\x03\x02\x07\x80").unwrap(),
+        "     0: BIT STRING, 2 bytes: 80 (7 unused bits)\n"
+    );
+    assert_eq!(
+        dump(b"\x9F\x64\x01\x00\x61\x00").unwrap(),
+        "     0: [100], 1 byte: 00\n     4: [APPLICATION 1], 0 bytes\n"
+    );
+}
+
+#[test]
+fn test_oid_name() {
+    assert_eq!(oid_name("1.2.840.113549.1.1.1"), Some("rsaEncryption"));
+    assert_eq!(oid_name("1.2.3"), None);
+}
+
+#[test]
+fn test_err_on_invalid_der() {
+    // what could be parsed is still printed
+    let mut output = Vec::new();
+    let error = der_dump(b"\x30\x05\x02\x01\x01\x02\x05", &mut output).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "invalid DER at offset 5: truncated content"
+    );
+    assert_eq!(
+        output,
+        b"     0: SEQUENCE, 5 bytes\n     2:   INTEGER, 1 byte: 1\n"
+    );
+
+    assert!(dump(b"\x30\x80\x00\x00").is_err());
+    assert!(dump(b"\x30\x03\x02\x01").is_err());
+    assert!(dump(b"\x04\x89\x01\x02\x03\x04\x05\x06\x07\x08\x09").is_err());
+    assert!(dump(&[0x30, 0x02].repeat(100)).is_err());
+}
@@ -0,0 +1,57 @@
+use base_util::*;
+
+fn diagnose(input: &[u8]) -> String {
+    let mut reader = ContextReader::new(input);
+    let err = b64_decode(&mut reader, &mut Vec::new(), false).unwrap_err();
+    reader.diagnose(err, "base64").to_string()
+}
+
+#[test]
+fn test_invalid_character() {
+    assert_eq!(
+        diagnose(b"YWJj\nY!Jj\n"),
+        "'!' is not in the base64 alphabet at line 2, column 2\n    Y!Jj\n     ^"
+    );
+}
+
+#[test]
+fn test_invalid_byte() {
+    assert_eq!(
+        diagnose(b"YW\x07Jj"),
+        "byte 0x07 is not in the base64 alphabet at line 1, column 3\n    YW.Jj\n      ^"
+    );
+}
+
+#[test]
+fn test_truncated_input() {
+    assert_eq!(
+        diagnose(b"YWJj\nYW"),
+        "input ends part way through a base64 word at line 2, column 3\n    YW\n      ^"
+    );
+
+    // the caret is at the reported position, even on a trailing blank line
+    assert_eq!(
+        diagnose(b"YWJj\nYW\n\n"),
+        "input ends part way through a base64 word at line 4, column 1\n    \n    ^"
+    );
+}
+
+#[test]
+fn test_long_line() {
+    let mut input = vec![b'A'; 200];
+    input[100] = b'!';
+    let expected = format!(
+        "'!' is not in the base64 alphabet at line 1, column 101\n    ...{}!{}...\n    {}^",
+        "A".repeat(60),
+        "A".repeat(19),
+        " ".repeat(63)
+    );
+    assert_eq!(diagnose(&input), expected);
+}
+
+#[test]
+fn test_other_errors_unchanged() {
+    let reader = ContextReader::new(&b""[..]);
+    let err = reader.diagnose(std::io::Error::other("boom"), "base64");
+    assert_eq!(err.to_string(), "boom");
+}
@@ -0,0 +1,63 @@
+use base_util::*;
+
+fn encode(input: &[u8], extended_hex: bool) -> Result<Vec<u8>, std::io::Error> {
+    let mut output = Vec::new();
+    b32_dns_encode(&mut &input[..], &mut output, extended_hex)?;
+    Ok(output)
+}
+
+fn decode(input: &[u8], extended_hex: bool) -> Result<Vec<u8>, std::io::Error> {
+    let mut output = Vec::new();
+    b32_dns_decode(&mut &input[..], &mut output, extended_hex)?;
+    Ok(output)
+}
+
+fn test_bidi(data: &[u8], encoded: &[u8], extended_hex: bool) {
+    assert_eq!(encode(data, extended_hex).unwrap(), encoded);
+    assert_eq!(decode(encoded, extended_hex).unwrap(), data);
+}
+
+#[test]
+fn test_empty() {
+    test_bidi(&[], &[], false);
+}
+
+#[test]
+fn test_hello_world() {
+    test_bidi(b"hello, world!", b"nbswy3dpfqqho33snrscc", false);
+    test_bidi(b"hello, world!", b"d1imor3f5gg7erridhi22", true);
+}
+
+#[test]
+fn test_labels() {
+    let data = [0xA5u8; 100];
+    let encoded = encode(&data, false).unwrap();
+    let labels: Vec<&[u8]> = encoded.split(|&c| c == b'.').collect();
+    assert_eq!(labels.len(), 3);
+    assert!(labels.iter().all(|label| label.len() <= 63));
+    assert_eq!(decode(&encoded, false).unwrap(), data.to_vec());
+}
+
+#[test]
+fn test_case_insensitive() {
+    assert_eq!(
+        decode(b"NbSwY3dPfQqHo33SnRsCc.", false).unwrap(),
+        b"hello, world!"
+    );
+}
+
+#[test]
+fn test_err_on_invalid_char() {
+    assert!(decode(b"nbswy3dpfqqho33snrsc1", false).is_err());
+    assert!(decode(b"d1imor3f5gg7erridhiw2", true).is_err());
+
+    // symbols outside the alphabet are invalid in either case
+    assert!(decode(b"NBSWY3DPFQQHO33SNRSC1", false).is_err());
+    assert!(decode(b"WWWWWWWW", true).is_err());
+    assert!(decode(b"D1IMOR3F5GG7ERRIDHIZ2", true).is_err());
+}
+
+#[test]
+fn test_err_on_invalid_length() {
+    assert!(decode(b"nbs", false).is_err());
+}
@@ -0,0 +1,77 @@
+use base_util::*;
+
+fn encode(input: &[u8], columns: usize, group_size: usize) -> Result<Vec<u8>, std::io::Error> {
+    let mut output = Vec::new();
+    hexdump_encode(&mut &input[..], &mut output, columns, group_size)?;
+    Ok(output)
+}
+
+fn decode(input: &[u8]) -> Result<Vec<u8>, std::io::Error> {
+    let mut output = Vec::new();
+    hexdump_decode(&mut &input[..], &mut output)?;
+    Ok(output)
+}
+
+fn test_bidi(data: &[u8], encoded: &[u8], columns: usize, group_size: usize) {
+    assert_eq!(encode(data, columns, group_size).unwrap(), encoded);
+    assert_eq!(decode(encoded).unwrap(), data);
+}
+
+#[test]
+fn test_empty() {
+    test_bidi(&[], &[], 16, 2);
+}
+
+#[test]
+fn test_hello_world() {
+    test_bidi(
+        b"hello, world!\n\x01\xff abc",
+        b"00000000: 6865 6c6c 6f2c 2077 6f72 6c64 210a 01ff  hello, world!...\n\
+          00000010: 2061 6263                                 abc\n",
+        16,
+        2,
+    );
+}
+
+#[test]
+fn test_grouping() {
+    test_bidi(b"hello", b"00000000: 68656c 6c6f  hello\n", 5, 3);
+    test_bidi(b"hello, w", b"00000000: 68656c6c6f2c2077  hello, w\n", 8, 0);
+}
+
+#[test]
+fn test_gutter_looks_like_hex() {
+    assert_eq!(decode(b"00000000: 6162 6364  abcd\n").unwrap(), b"abcd");
+}
+
+#[test]
+fn test_offset_gaps() {
+    assert_eq!(
+        decode(b"00000000: 6162  ab\n00000004: 6364  cd\n").unwrap(),
+        b"ab\0\0cd"
+    );
+    assert!(decode(b"00000004: 6364  cd\n00000000: 6162  ab\n").is_err());
+
+    // gaps are streamed, so a huge offset fails on writing rather than allocating
+    let mut output = [0xAA; 16];
+    let error = hexdump_decode(&mut &b"ffffffffff: 00\n"[..], &mut &mut output[..]).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::WriteZero);
+    assert_eq!(output, [0; 16]);
+}
+
+#[test]
+fn test_plain() {
+    let mut encoded = Vec::new();
+    hexdump_plain_encode(&mut &b"hello, world!"[..], &mut encoded, 4).unwrap();
+    assert_eq!(encoded, b"68656c6c\n6f2c2077\n6f726c64\n21\n");
+
+    let mut decoded = Vec::new();
+    hexdump_plain_decode(&mut encoded.as_slice(), &mut decoded).unwrap();
+    assert_eq!(decoded, b"hello, world!");
+}
+
+#[test]
+fn test_err_on_invalid_hex() {
+    assert!(decode(b"00000000: 6g62  ab\n").is_err());
+    assert!(hexdump_plain_decode(&mut &b"abc"[..], &mut Vec::new()).is_err());
+}
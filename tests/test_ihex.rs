@@ -0,0 +1,93 @@
+use base_util::*;
+
+fn encode(
+    input: &[u8],
+    base_address: u32,
+    record_length: usize,
+) -> Result<Vec<u8>, std::io::Error> {
+    let mut output = Vec::new();
+    ihex_encode(&mut &input[..], &mut output, base_address, record_length)?;
+    Ok(output)
+}
+
+fn decode(input: &[u8]) -> Result<Vec<u8>, std::io::Error> {
+    let mut output = Vec::new();
+    ihex_decode(&mut &input[..], &mut output)?;
+    Ok(output)
+}
+
+fn test_bidi(data: &[u8], encoded: &[u8], base_address: u32, record_length: usize) {
+    assert_eq!(encode(data, base_address, record_length).unwrap(), encoded);
+    assert_eq!(decode(encoded).unwrap(), data);
+}
+
+#[test]
+fn test_empty() {
+    test_bidi(&[], b":00000001FF\n", 0, 16);
+}
+
+#[test]
+fn test_hello_world() {
+    test_bidi(
+        b"hello, world!",
+        b":0801000068656C6C6F2C207720\n:050108006F726C642120\n:00000001FF\n",
+        0x100,
+        8,
+    );
+}
+
+#[test]
+fn test_extended_linear_address() {
+    test_bidi(
+        b"abcd",
+        b":020000040800F2\n:02FFFE0061623E\n:020000040801F1\n:02000000636437\n:00000001FF\n",
+        0x0800FFFE,
+        16,
+    );
+}
+
+#[test]
+fn test_extended_segment_address_and_gaps() {
+    assert_eq!(
+        decode(b":020000021000EC\n:01000000619E\n:01000200629B\n:00000001FF\n").unwrap(),
+        b"a\xFFb"
+    );
+}
+
+#[test]
+fn test_streamed_input() {
+    // records are filled however the input is split into reads
+    let data: Vec<u8> = (0..100).collect();
+    let mut output = Vec::new();
+    let mut reader = std::io::Read::chain(&data[0..5], &data[5..]);
+    ihex_encode(&mut reader, &mut output, 0xFFF8, 16).unwrap();
+    assert_eq!(output, encode(&data, 0xFFF8, 16).unwrap());
+    assert_eq!(output.iter().filter(|&&c| c == b'\n').count(), 9);
+}
+
+#[test]
+fn test_err_on_sparse_image() {
+    // records at either end of the address space would need a 4 GiB image
+    let error =
+        decode(b":01000000619E\n:02000004FFFFFC\n:01FFF00062AE\n:00000001FF\n").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "image from 0x00000000 to 0xFFFFFFF1 is too large to fill gaps in (limit is 64 MiB)"
+    );
+}
+
+#[test]
+fn test_err_on_bad_checksum() {
+    assert!(decode(b":0100000061FF\n:00000001FF\n").is_err());
+}
+
+#[test]
+fn test_err_on_missing_end_of_file() {
+    assert!(decode(b":01000000619E\n").is_err());
+}
+
+#[test]
+fn test_invalid_record_length() {
+    assert!(encode(b"hello", 0, 0).is_err());
+    assert!(encode(b"hello", 0, 256).is_err());
+}
@@ -0,0 +1,109 @@
+use base_util::*;
+
+// {"alg":"none"}.{"iat":951782400,"nbf":-86401,"exp":1.5,"x":[1,{"iat":0}]}.
+const UNSECURED: &str = "eyJhbGciOiJub25lIn0.eyJpYXQiOjk1MTc4MjQwMCwibmJmIjotODY0MDEsImV4cCI6MS41LCJ4IjpbMSx7ImlhdCI6MH1dfQ.";
+
+fn inspect(token: &str) -> (String, Result<(), std::io::Error>) {
+    let mut output = Vec::new();
+    let result = jwt_inspect(&mut token.as_bytes(), &mut output);
+    (String::from_utf8(output).unwrap(), result)
+}
+
+#[test]
+fn test_split() {
+    let segments = jwt_split(UNSECURED.as_bytes()).unwrap();
+    assert_eq!(segments.len(), 3);
+    assert_eq!(segments[0].offset, 0);
+    assert_eq!(segments[0].data, Ok(b"{\"alg\":\"none\"}".to_vec()));
+    assert_eq!(segments[1].offset, 20);
+    assert_eq!(segments[2].offset, 99);
+    assert_eq!(segments[2].data, Ok(Vec::new()));
+
+    // JWEs have five segments
+    assert_eq!(jwt_split(b"e30.AQ.Ag.Aw.BA").unwrap().len(), 5);
+    assert!(jwt_split(b"e30.e30").is_err());
+    assert!(jwt_split(b"e30.e30.e30.e30").is_err());
+}
+
+#[test]
+fn test_err_on_malformed_segment() {
+    // padding, whitespace and the standard alphabet are not allowed
+    for token in ["e30=.e30.", "e30.e3 0.", "e30.e30.a+b"] {
+        let segments = jwt_split(token.as_bytes()).unwrap();
+        assert!(segments.iter().any(|segment| segment.data.is_err()));
+    }
+
+    let segments = jwt_split(b"e30.e30.AB!C").unwrap();
+    assert_eq!(segments[2].data.as_ref().unwrap_err().position().offset, 10);
+}
+
+#[test]
+fn test_inspect() {
+    let (output, result) = inspect(&format!("{}\n", UNSECURED));
+    result.unwrap();
+    assert_eq!(
+        output,
+        "Header:\n\
+        {\n\
+        \x20 \"alg\": \"none\"\n\
+        }\n\
+        \n\
+        Payload:\n\
+        {\n\
+        \x20 \"iat\": 951782400,  // 2000-02-29 00:00:00 UTC\n\
+        \x20 \"nbf\": -86401,  // 1969-12-30 23:59:59 UTC\n\
+        \x20 \"exp\": 1.5,\n\
+        \x20 \"x\": [\n\
+        \x20   1,\n\
+        \x20   {\n\
+        \x20     \"iat\": 0\n\
+        \x20   }\n\
+        \x20 ]\n\
+        }\n\
+        \n\
+        Signature: 0 bytes\n"
+    );
+
+    let (output, result) = inspect("e30.AQ.Ag.Aw.BA");
+    result.unwrap();
+    assert_eq!(
+        output,
+        "Header:\n{}\n\n\
+        Encrypted key: 1 bytes\n01\n\n\
+        Initialization vector: 1 bytes\n02\n\n\
+        Ciphertext: 1 bytes\n03\n\n\
+        Authentication tag: 1 bytes\n04\n"
+    );
+}
+
+#[test]
+fn test_inspect_malformed() {
+    // everything is printed, and the first error is located within the input
+    let (output, result) = inspect("\n  e30.e!0.AQ");
+    assert_eq!(
+        output,
+        "Header:\n{}\n\n\
+        Payload: malformed, invalid character '!' at line 2, column 8 (offset 8)\n\n\
+        Signature: 1 bytes\n01\n"
+    );
+    let error = result.unwrap_err();
+    let position = error
+        .get_ref()
+        .unwrap()
+        .downcast_ref::<DecodeError>()
+        .unwrap()
+        .position();
+    assert_eq!((position.offset, position.line, position.column), (8, 2, 8));
+    let (output, _) = inspect("  \n eyJ9.e30.xx!x");
+    assert!(output.ends_with(
+        "Signature: malformed, invalid character '!' at line 2, column 13 (offset 15)\n"
+    ));
+
+    // JSON errors are flagged by their offset in the decoded segment
+    let (output, result) = inspect("eyJhIjp9.e30.");
+    assert!(output.starts_with("Header: malformed, invalid JSON at offset 5 of decoded segment\n"));
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "invalid JWT: header is not JSON (offset 5 of decoded segment)"
+    );
+}
@@ -0,0 +1,97 @@
+use base_util::*;
+use std::io::Write;
+
+fn wrap(wrapper: LineWrapper<Vec<u8>>, fragments: &[&[u8]]) -> Vec<u8> {
+    let mut wrapper = wrapper;
+    for fragment in fragments {
+        wrapper.write_all(fragment).unwrap();
+    }
+    wrapper.finish().unwrap()
+}
+
+#[test]
+fn test_wrapping() {
+    let wrapper = LineWrapper::new(Vec::new(), Some(4)).unwrap();
+    assert_eq!(wrap(wrapper, &[b"abcdefghij"]), b"abcd\nefgh\nij\n");
+
+    // a full line is not ended until more data follows
+    let wrapper = LineWrapper::new(Vec::new(), Some(4)).unwrap();
+    assert_eq!(wrap(wrapper, &[b"abcdefgh"]), b"abcd\nefgh\n");
+
+    // the column is tracked across writes
+    let wrapper = LineWrapper::new(Vec::new(), Some(4)).unwrap();
+    assert_eq!(
+        wrap(wrapper, &[b"ab", b"", b"cde", b"f", b"ghij"]),
+        b"abcd\nefgh\nij\n"
+    );
+
+    let wrapper = LineWrapper::new(Vec::new(), None).unwrap();
+    assert_eq!(wrap(wrapper, &[b"abcdefghij"]), b"abcdefghij\n");
+
+    // there is no final line to end if nothing was written
+    let wrapper = LineWrapper::new(Vec::new(), Some(4)).unwrap();
+    assert_eq!(wrap(wrapper, &[]), b"");
+    let wrapper = LineWrapper::new(Vec::new(), None).unwrap().indent(2);
+    assert_eq!(wrap(wrapper, &[b""]), b"");
+
+    assert!(LineWrapper::new(Vec::new(), Some(0)).is_err());
+}
+
+#[test]
+fn test_column() {
+    let mut wrapper = LineWrapper::new(Vec::new(), Some(4)).unwrap().indent(2);
+    assert_eq!(wrapper.column(), 0);
+    wrapper.write_all(b"abc").unwrap();
+    assert_eq!(wrapper.column(), 3);
+    wrapper.write_all(b"def").unwrap();
+    assert_eq!(wrapper.column(), 2);
+}
+
+#[test]
+fn test_prefix_and_separator() {
+    let wrapper = LineWrapper::new(Vec::new(), Some(4)).unwrap().indent(2);
+    assert_eq!(wrap(wrapper, &[b"abcdefghij"]), b"  abcd\n  efgh\n  ij\n");
+
+    let wrapper = LineWrapper::new(Vec::new(), Some(4))
+        .unwrap()
+        .prefix(b"\t\"")
+        .separator(b"\" +\n");
+    assert_eq!(wrap(wrapper, &[b"abcdef"]), b"\t\"abcd\" +\n\t\"ef\" +\n");
+
+    let wrapper = LineWrapper::new(Vec::new(), Some(4))
+        .unwrap()
+        .separator(b"\r\n")
+        .final_newline(false);
+    assert_eq!(wrap(wrapper, &[b"abcdefghij"]), b"abcd\r\nefgh\r\nij");
+
+    // nothing is written for lines that have no data
+    let wrapper = LineWrapper::new(Vec::new(), Some(4))
+        .unwrap()
+        .indent(2)
+        .final_newline(false);
+    assert_eq!(wrap(wrapper, &[b"", b""]), b"");
+}
+
+#[test]
+fn test_dropped() {
+    let mut output = Vec::new();
+    {
+        let mut wrapper = LineWrapper::new(&mut output, Some(4)).unwrap().indent(1);
+        wrapper.write_all(b"abcdef").unwrap();
+    }
+    assert_eq!(output, b" abcd\n ef\n");
+}
+
+#[test]
+fn test_encoded_block_scalar() {
+    // base-64 indented under a YAML key
+    let mut output = b"key: |\n".to_vec();
+    let wrapper = LineWrapper::new(&mut output, Some(16)).unwrap().indent(4);
+    let mut writer = B64EncoderWriter::new(wrapper, None).unwrap();
+    writer.write_all(b"Hello, world! Hello, world!").unwrap();
+    writer.finish().unwrap().finish().unwrap();
+    assert_eq!(
+        output,
+        b"key: |\n    SGVsbG8sIHdvcmxk\n    ISBIZWxsbywgd29y\n    bGQh\n"
+    );
+}
@@ -0,0 +1,101 @@
+use base_util::*;
+
+fn encode(
+    input: &[u8],
+    charset: &str,
+    encoding: MimeWordEncoding,
+) -> Result<Vec<u8>, std::io::Error> {
+    let mut output = Vec::new();
+    mime_header_encode(&mut &input[..], &mut output, charset, encoding)?;
+    Ok(output)
+}
+
+fn decode(input: &[u8]) -> Result<Vec<u8>, std::io::Error> {
+    let mut output = Vec::new();
+    mime_header_decode(&mut &input[..], &mut output)?;
+    Ok(output)
+}
+
+fn test_encode(input: &[u8], expected: &[u8], encoding: MimeWordEncoding) {
+    assert_eq!(encode(input, "UTF-8", encoding).unwrap(), expected);
+}
+
+fn test_decode(input: &[u8], expected: &[u8]) {
+    assert_eq!(decode(input).unwrap(), expected);
+}
+
+#[test]
+fn test_empty() {
+    test_encode(b"", b"", MimeWordEncoding::B);
+    test_decode(b"", b"");
+}
+
+#[test]
+fn test_b_encoding() {
+    test_encode(
+        "Grüße".as_bytes(),
+        b"=?UTF-8?B?R3LDvMOfZQ==?=",
+        MimeWordEncoding::B,
+    );
+    test_decode(b"=?UTF-8?B?R3LDvMOfZQ==?=", "Grüße".as_bytes());
+}
+
+#[test]
+fn test_q_encoding() {
+    test_encode(
+        "a b=ö?".as_bytes(),
+        b"=?UTF-8?Q?a_b=3D=C3=B6=3F?=",
+        MimeWordEncoding::Q,
+    );
+    test_decode(b"=?utf-8?q?a_b=3D=C3=B6=3F?=", "a b=ö?".as_bytes());
+}
+
+#[test]
+fn test_folding() {
+    let text = "Grüße aus Köln, schöne Grüße an alle die das hier lesen können!".as_bytes();
+    for &encoding in &[MimeWordEncoding::B, MimeWordEncoding::Q] {
+        let encoded = encode(text, "UTF-8", encoding).unwrap();
+        let words: Vec<&[u8]> = encoded.split(|&c| c == b'\n').collect();
+        assert!(words.len() > 1);
+        for word in &words {
+            assert!(word.len() <= 76, "word too long: {:?}", word);
+        }
+        assert_eq!(decode(&encoded).unwrap(), text);
+    }
+}
+
+#[test]
+fn test_utf8_characters_not_split() {
+    let text = "ö".repeat(40);
+    let encoded = encode(text.as_bytes(), "UTF-8", MimeWordEncoding::B).unwrap();
+    for word in encoded.split(|&c| c == b' ') {
+        let word = String::from_utf8(word.to_vec()).unwrap();
+        let payload = word
+            .trim()
+            .trim_start_matches("=?UTF-8?B?")
+            .trim_end_matches("?=");
+        let decoded = decode(format!("=?UTF-8?B?{}?=", payload).as_bytes()).unwrap();
+        assert!(String::from_utf8(decoded).is_ok());
+    }
+}
+
+#[test]
+fn test_adjacent_words_joined() {
+    test_decode(b"=?UTF-8?Q?a?= =?UTF-8?Q?b?=", b"ab");
+    test_decode(b"=?UTF-8?Q?a?=\r\n =?UTF-8?Q?b?=", b"ab");
+    test_decode(b"=?UTF-8?Q?a?= x =?UTF-8?Q?b?=", b"a x b");
+}
+
+#[test]
+fn test_malformed_words_passed_through() {
+    test_decode(
+        b"Subject: =?UTF-8?X?abc?= =?UTF-8?Q?a b?=",
+        b"Subject: =?UTF-8?X?abc?= =?UTF-8?Q?a b?=",
+    );
+}
+
+#[test]
+fn test_invalid_charset() {
+    assert!(encode(b"hello", "UTF?8", MimeWordEncoding::B).is_err());
+    assert!(encode(b"hello", "", MimeWordEncoding::B).is_err());
+}
@@ -0,0 +1,60 @@
+use base_util::*;
+
+fn encode(input: &[u8]) -> Result<Vec<u8>, std::io::Error> {
+    let mut output = Vec::new();
+    proquint_encode(&mut &input[..], &mut output)?;
+    Ok(output)
+}
+
+fn decode(input: &[u8]) -> Result<Vec<u8>, std::io::Error> {
+    let mut output = Vec::new();
+    proquint_decode(&mut &input[..], &mut output)?;
+    Ok(output)
+}
+
+fn test_bidi(data: &[u8], encoded: &[u8]) {
+    assert_eq!(encode(data).unwrap(), encoded);
+    assert_eq!(decode(encoded).unwrap(), data);
+}
+
+#[test]
+fn test_empty() {
+    test_bidi(&[], &[]);
+}
+
+#[test]
+fn test_ip_addresses() {
+    test_bidi(&[127, 0, 0, 1], b"lusab-babad");
+    test_bidi(&[63, 84, 220, 193], b"gutih-tugad");
+    test_bidi(&[255, 255, 255, 255], b"zuzuz-zuzuz");
+}
+
+#[test]
+fn test_streamed_input() {
+    // pairs and quints may be split between reads
+    let mut output = Vec::new();
+    let mut reader = std::io::Read::chain(&[127, 0, 0][..], &[1][..]);
+    proquint_encode(&mut reader, &mut output).unwrap();
+    assert_eq!(output, b"lusab-babad");
+
+    let mut output = Vec::new();
+    let mut reader = std::io::Read::chain(&b"lusab-ba"[..], &b"bad"[..]);
+    proquint_decode(&mut reader, &mut output).unwrap();
+    assert_eq!(output, [127, 0, 0, 1]);
+}
+
+#[test]
+fn test_separators_and_case() {
+    assert_eq!(decode(b"LUSAB babad\n").unwrap(), &[127, 0, 0, 1]);
+}
+
+#[test]
+fn test_err_on_invalid_quint() {
+    assert!(decode(b"lusab-babae").is_err());
+    assert!(decode(b"lusab-baba").is_err());
+}
+
+#[test]
+fn test_err_on_odd_length() {
+    assert!(encode(&[1, 2, 3]).is_err());
+}
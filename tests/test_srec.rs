@@ -0,0 +1,71 @@
+use base_util::*;
+
+fn encode(
+    input: &[u8],
+    base_address: u32,
+    record_length: usize,
+) -> Result<Vec<u8>, std::io::Error> {
+    let mut output = Vec::new();
+    srec_encode(&mut &input[..], &mut output, base_address, record_length)?;
+    Ok(output)
+}
+
+fn decode(input: &[u8]) -> Result<Vec<u8>, std::io::Error> {
+    let mut output = Vec::new();
+    srec_decode(&mut &input[..], &mut output)?;
+    Ok(output)
+}
+
+fn test_bidi(data: &[u8], encoded: &[u8], base_address: u32, record_length: usize) {
+    assert_eq!(encode(data, base_address, record_length).unwrap(), encoded);
+    assert_eq!(decode(encoded).unwrap(), data);
+}
+
+#[test]
+fn test_empty() {
+    test_bidi(&[], b"S0030000FC\nS5030000FC\nS9030000FC\n", 0, 16);
+}
+
+#[test]
+fn test_hello_world() {
+    test_bidi(
+        b"hello, world!",
+        b"S0030000FC\nS10B010068656C6C6F2C20771C\nS10801086F726C64211C\nS5030002FA\nS9030100FB\n",
+        0x100,
+        8,
+    );
+}
+
+#[test]
+fn test_address_sizes() {
+    let s2 = encode(b"abc", 0x10000, 16).unwrap();
+    assert!(s2.starts_with(b"S0030000FC\nS2"));
+    assert!(s2.ends_with(b"S804010000FA\n"));
+    let s3 = encode(b"abc", 0x08000000, 16).unwrap();
+    assert!(s3.starts_with(b"S0030000FC\nS3"));
+    assert_eq!(decode(&s3).unwrap(), b"abc");
+
+    // the size is chosen by where the image ends, not where it starts
+    let data = [0x55; 17];
+    assert!(encode(&data[0..16], 0xFFF0, 16)
+        .unwrap()
+        .ends_with(b"S903FFF00D\n"));
+    let s2 = encode(&data, 0xFFF0, 16).unwrap();
+    assert!(s2.ends_with(b"S80400FFF00C\n"));
+    assert_eq!(decode(&s2).unwrap(), data);
+}
+
+#[test]
+fn test_err_on_bad_checksum() {
+    assert!(decode(b"S1040000610A\nS9030000FC\n").is_err());
+}
+
+#[test]
+fn test_err_on_record_count_mismatch() {
+    assert!(decode(b"S1040000619A\nS5030002FA\nS9030000FC\n").is_err());
+}
+
+#[test]
+fn test_err_on_missing_termination() {
+    assert!(decode(b"S0030000FC\n").is_err());
+}
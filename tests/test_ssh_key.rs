@@ -0,0 +1,94 @@
+use base_util::*;
+
+const ED25519: &str = "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIBOAqvjezEeiBn+8GxoTsB9jtzyyNKF2xWYScZ7AUJLM me@host ed25519";
+const ECDSA: &str = "ecdsa-sha2-nistp256 AAAAE2VjZHNhLXNoYTItbmlzdHAyNTYAAAAIbmlzdHAyNTYAAABBBMg8H3GHnEdgkTbTqy+U9QooFDvs5g23bERiqQjR15MuH1Jkeq6XZWNIFsT3sWoCGtRajPHGANUfWTZ0NhQebO0=";
+
+#[test]
+fn test_parse() {
+    let key = ssh_key_parse(ED25519).unwrap();
+    assert_eq!(key.options, None);
+    assert_eq!(key.key_type, "ssh-ed25519");
+    assert_eq!(key.bits, Some(256));
+    assert_eq!(key.blob.len(), 51);
+    assert_eq!(key.comment.as_deref(), Some("me@host ed25519"));
+    assert_eq!(key.algorithm(), "ED25519");
+
+    let key = ssh_key_parse(ECDSA).unwrap();
+    assert_eq!(key.bits, Some(256));
+    assert_eq!(key.comment, None);
+    assert_eq!(key.algorithm(), "ECDSA");
+}
+
+#[test]
+fn test_fingerprints() {
+    let key = ssh_key_parse(ED25519).unwrap();
+    assert_eq!(
+        key.fingerprint_md5(),
+        "MD5:5b:f5:21:0d:d7:92:26:ca:c2:1f:8a:c9:01:64:f4:be"
+    );
+    let key = ssh_key_parse(ECDSA).unwrap();
+    assert_eq!(
+        key.fingerprint_sha256(),
+        "SHA256:AMVehGQG5qissBZ/42RAlFzWKGe+2zOHAOVeSqB/mHc"
+    );
+}
+
+#[test]
+fn test_authorized_keys() {
+    let input = format!(
+        "# keys\n\
+        \n\
+        command=\"echo a b\",no-pty {}\n\
+        \x20 {}\r\n",
+        ED25519, ECDSA
+    );
+    let keys = ssh_keys_parse(&mut input.as_bytes()).unwrap();
+    assert_eq!(keys.len(), 2);
+    assert_eq!(
+        keys[0].options.as_deref(),
+        Some("command=\"echo a b\",no-pty")
+    );
+    assert_eq!(keys[0].key_type, "ssh-ed25519");
+    assert_eq!(keys[1].key_type, "ecdsa-sha2-nistp256");
+
+    // lines may be split between reads
+    let (first, second) = input.as_bytes().split_at(100);
+    let mut reader = std::io::Read::chain(first, second);
+    assert_eq!(ssh_keys_parse(&mut reader).unwrap(), keys);
+}
+
+#[test]
+fn test_err_on_invalid_key() {
+    assert!(ssh_key_parse("").is_err());
+    assert!(ssh_key_parse("ssh-ed25519").is_err());
+
+    // the key data must be complete and match the type
+    let error = ssh_key_parse(&ED25519.replace("ssh-ed25519", "ssh-rsa")).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "key type ssh-rsa does not match key data type ssh-ed25519"
+    );
+    assert!(ssh_key_parse("ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIBOA").is_err());
+
+    // base-64 errors are located within the whole input
+    let error = ssh_keys_parse(&mut "\n  ssh-ed25519 AAAA!AAA\n".as_bytes()).unwrap_err();
+    let position = error
+        .get_ref()
+        .unwrap()
+        .downcast_ref::<DecodeError>()
+        .unwrap()
+        .position();
+    assert_eq!(
+        (position.offset, position.line, position.column),
+        (19, 2, 19)
+    );
+    let error = ssh_keys_parse(&mut "\nssh-ed25519 AAAA\n".as_bytes()).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "invalid key on line 2: truncated key data"
+    );
+
+    // a length too large for the data, however large, is truncation
+    let error = ssh_key_parse("ssh-ed25519 /////w==").unwrap_err();
+    assert!(error.to_string().ends_with("truncated key data"));
+}